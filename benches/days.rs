@@ -0,0 +1,59 @@
+use aoc2020::days::{Registration, Registry};
+use aoc2020::generate::{self, Random};
+use aoc2020::runner;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use std::io;
+
+fn lines(input: &str) -> impl Iterator<Item = io::Result<String>> + '_ {
+    input.lines().map(|line| Ok(line.to_string()))
+}
+
+// Times reading and solving separately, with a fresh solver for every run.
+fn bench_solver(c: &mut Criterion, name: &str, registration: &Registration, input: &str, sample_size: usize) {
+    let mut group = c.benchmark_group(name);
+    group.sample_size(sample_size);
+    group.bench_function("parse", |b| {
+        b.iter_batched(|| (registration.solver)(),
+                       |mut solver| solver.read(&mut lines(input)).unwrap(),
+                       BatchSize::LargeInput)
+    });
+    group.bench_function("solve", |b| {
+        b.iter_batched(|| {
+                           let mut solver = (registration.solver)();
+                           solver.read(&mut lines(input)).unwrap();
+                           solver
+                       },
+                       |mut solver| solver.solve().unwrap(),
+                       BatchSize::LargeInput)
+    });
+    group.finish();
+}
+
+fn checked_in_inputs(c: &mut Criterion) {
+    let registry = Registry::new();
+    for registration in registry.registrations() {
+        let input_file_path =
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(runner::input_file_path(registry.year(), registration.day_index));
+        let input = std::fs::read_to_string(&input_file_path).unwrap();
+        let name = format!("day_{}/part_{}", registration.day_index, registration.part_index);
+        // Day 15 plays out up to 30 million turns, so take fewer samples of it.
+        let sample_size = if registration.day_index == 15 { 10 } else { 100 };
+        bench_solver(c, &name, registration, &input, sample_size);
+    }
+}
+
+fn synthetic_inputs(c: &mut Criterion) {
+    let registry = Registry::new();
+    let day_2 = generate::day_2(&mut Random::new(2020), 1_000_000);
+    // Day 8 part 2 reruns the program once for each instruction it could repair, so it's quadratic in the size.
+    let day_8 = generate::day_8_looping(&mut Random::new(8), 5_000);
+    for part_index in 1..=2 {
+        bench_solver(c, &format!("synthetic/day_2/part_{}", part_index),
+                     registry.find(2, part_index).unwrap(), &day_2, 10);
+        bench_solver(c, &format!("synthetic/day_8/part_{}", part_index),
+                     registry.find(8, part_index).unwrap(), &day_8, 10);
+    }
+}
+
+criterion_group!(benches, checked_in_inputs, synthetic_inputs);
+criterion_main!(benches);
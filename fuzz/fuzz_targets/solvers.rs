@@ -0,0 +1,39 @@
+#![no_main]
+
+// Run with: cargo +nightly fuzz run solvers
+// Feeds arbitrary bytes to every solver. Any panic is a bug, since bad input should be a parse error.
+
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+use libfuzzer_sys::fuzz_target;
+use aoc2020::days::Registry;
+use aoc2020::report;
+use aoc2020::solve::{Cancellation, Progress};
+
+// Day 15 part 2 always plays out 30 million turns, whatever the input, so it's cancelled after this long.
+// Every other solver runs until it's done, on the fuzzing thread, so libFuzzer sees any panic.
+const SLOW_SOLVE_TIME_LIMIT: Duration = Duration::from_millis(20);
+
+fuzz_target!(|data: &[u8]| {
+    let input = String::from_utf8_lossy(data);
+    let registry = Registry::new();
+    for registration in registry.registrations() {
+        let cancellation = Cancellation::new();
+        let mut lines = input.lines().map(|line| Ok(line.to_string()));
+        if registration.day_index == 15 && registration.part_index == 2 {
+            std::thread::scope(|scope| {
+                let (sender, receiver) = mpsc::channel::<()>();
+                let timer_cancellation = cancellation.clone();
+                scope.spawn(move || {
+                    if let Err(RecvTimeoutError::Timeout) = receiver.recv_timeout(SLOW_SOLVE_TIME_LIMIT) {
+                        timer_cancellation.cancel();
+                    }
+                });
+                report::run_with(registration, &mut lines, cancellation, Progress::new());
+                drop(sender);
+            });
+        } else {
+            report::run_with(registration, &mut lines, cancellation, Progress::new());
+        }
+    }
+});
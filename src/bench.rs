@@ -0,0 +1,273 @@
+use std::fmt;
+use std::io;
+use std::time::{Duration, Instant};
+use crate::days::Registration;
+use crate::runner::RunError;
+
+pub struct Statistics {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub p95: Duration,
+}
+
+impl Statistics {
+    pub fn from_samples(samples: &[Duration]) -> Statistics {
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let rank = |fraction: f64| {
+            let index = ((fraction * sorted.len() as f64).ceil() as usize).max(1) - 1;
+            sorted[index.min(sorted.len() - 1)]
+        };
+        let total: Duration = sorted.iter().sum();
+        Statistics {
+            min: sorted[0],
+            median: rank(0.5),
+            mean: total / (sorted.len() as u32),
+            p95: rank(0.95),
+        }
+    }
+}
+
+pub struct Measurement {
+    pub day_index: u32,
+    pub part_index: u32,
+    pub title: &'static str,
+    pub parse: Statistics,
+    pub solve: Statistics,
+}
+
+// Times reading and solving separately, making a fresh solver for every iteration.
+pub fn measure(registration: &Registration, input: &str, iterations: usize) -> Result<Measurement, RunError> {
+    let mut parse_samples = Vec::with_capacity(iterations);
+    let mut solve_samples = Vec::with_capacity(iterations);
+    for _ in 0..iterations.max(1) {
+        let mut solver = (registration.solver)();
+        let mut lines = input.lines().map(|line| Ok(line.to_string()));
+        let parse_start = Instant::now();
+        solver.read(&mut lines)?;
+        parse_samples.push(parse_start.elapsed());
+        let solve_start = Instant::now();
+        solver.solve()?;
+        solve_samples.push(solve_start.elapsed());
+    }
+    Ok(
+        Measurement {
+            day_index: registration.day_index,
+            part_index: registration.part_index,
+            title: registration.title,
+            parse: Statistics::from_samples(&parse_samples),
+            solve: Statistics::from_samples(&solve_samples),
+        }
+    )
+}
+
+// The median times of earlier runs, to compare against. Runs of different years, days and parts can share a baseline.
+pub struct Baseline {
+    entries: Vec<BaselineEntry>,
+}
+
+struct BaselineEntry {
+    year: u32,
+    day_index: u32,
+    part_index: u32,
+    parse_median: Duration,
+    solve_median: Duration,
+}
+
+impl BaselineEntry {
+    // A line like "<year> <day> <part> <parse median> <solve median>", with the medians in nanoseconds.
+    fn parse(line: &str) -> Option<BaselineEntry> {
+        let fields: Vec<&str> = line.split(' ').collect();
+        match fields[..] {
+            [year, day_index, part_index, parse_median, solve_median] =>
+                Some(
+                    BaselineEntry {
+                        year: year.parse().ok()?,
+                        day_index: day_index.parse().ok()?,
+                        part_index: part_index.parse().ok()?,
+                        parse_median: Duration::from_nanos(parse_median.parse().ok()?),
+                        solve_median: Duration::from_nanos(solve_median.parse().ok()?),
+                    }
+                ),
+            _ => None,
+        }
+    }
+
+    fn key(&self) -> (u32, u32, u32) {
+        (self.year, self.day_index, self.part_index)
+    }
+}
+
+impl fmt::Display for BaselineEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {} {} {}", self.year, self.day_index, self.part_index,
+               self.parse_median.as_nanos(), self.solve_median.as_nanos())
+    }
+}
+
+impl Baseline {
+
+    fn parse(text: &str) -> io::Result<Baseline> {
+        let entries =
+            text.lines().map(|line| {
+                BaselineEntry::parse(line).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, format!("invalid baseline line: '{}'", line))
+                })
+            }).collect::<io::Result<Vec<BaselineEntry>>>()?;
+        Ok(Baseline { entries })
+    }
+
+    pub fn load(path: &std::path::Path) -> io::Result<Baseline> {
+        Baseline::parse(&std::fs::read_to_string(path)?)
+    }
+
+    // Adds the medians of the measurements, replacing any the baseline already has for the same solvers.
+    fn record(&mut self, year: u32, measurements: &[Measurement]) {
+        for m in measurements {
+            let entry =
+                BaselineEntry {
+                    year,
+                    day_index: m.day_index,
+                    part_index: m.part_index,
+                    parse_median: m.parse.median,
+                    solve_median: m.solve.median,
+                };
+            self.entries.retain(|e| e.key() != entry.key());
+            self.entries.push(entry);
+        }
+        self.entries.sort_by_key(BaselineEntry::key);
+    }
+
+    // Writes one line per solver, ordered by year, day and part. Solvers that weren't measured this time keep the
+    // medians already saved for them, so a baseline can be built up a few days at a time.
+    pub fn save(year: u32, measurements: &[Measurement], path: &std::path::Path) -> io::Result<()> {
+        let mut baseline =
+            match Baseline::load(path) {
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Baseline { entries: Vec::new() },
+                result => result?,
+            };
+        baseline.record(year, measurements);
+        std::fs::write(path, baseline.to_string())
+    }
+
+    // Returns the parse and solve medians of a solver, if the baseline has it.
+    pub fn medians(&self, year: u32, day_index: u32, part_index: u32) -> Option<(Duration, Duration)> {
+        self.entries.iter()
+            .find(|e| e.key() == (year, day_index, part_index))
+            .map(|e| (e.parse_median, e.solve_median))
+    }
+
+}
+
+impl fmt::Display for Baseline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            writeln!(f, "{}", entry)?;
+        }
+        Ok(())
+    }
+}
+
+// How much slower (positive) or faster (negative) a time is than the baseline, in percent.
+pub fn change_percent(baseline: Duration, time: Duration) -> f64 {
+    if baseline.is_zero() {
+        return 0.0;
+    }
+    100.0 * (time.as_secs_f64() - baseline.as_secs_f64()) / baseline.as_secs_f64()
+}
+
+// Formats a duration with a unit that suits its size.
+pub struct Short(pub Duration);
+
+impl fmt::Display for Short {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let nanos = self.0.as_nanos() as f64;
+        let text =
+            if nanos < 1e3 {
+                format!("{}ns", nanos)
+            } else if nanos < 1e6 {
+                format!("{:.1}µs", nanos / 1e3)
+            } else if nanos < 1e9 {
+                format!("{:.2}ms", nanos / 1e6)
+            } else {
+                format!("{:.3}s", nanos / 1e9)
+            };
+        f.pad(&text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn milliseconds(values: impl Iterator<Item = u64>) -> Vec<Duration> {
+        values.map(Duration::from_millis).collect()
+    }
+
+    fn measurement(day_index: u32, part_index: u32, parse_median: u64, solve_median: u64) -> Measurement {
+        Measurement {
+            day_index,
+            part_index,
+            title: "Title",
+            parse: Statistics::from_samples(&milliseconds(std::iter::once(parse_median))),
+            solve: Statistics::from_samples(&milliseconds(std::iter::once(solve_median))),
+        }
+    }
+
+    #[test]
+    fn statistics() {
+        // In any order, since they're sorted first.
+        let samples = milliseconds((1..=20).rev());
+        let statistics = Statistics::from_samples(&samples);
+        let values = [statistics.min, statistics.median, statistics.mean, statistics.p95];
+        assert_eq!(values, [Duration::from_millis(1), Duration::from_millis(10), Duration::from_micros(10500),
+                            Duration::from_millis(19)]);
+        let statistics = Statistics::from_samples(&milliseconds(std::iter::once(7)));
+        let values = [statistics.min, statistics.median, statistics.mean, statistics.p95];
+        assert_eq!(values, [Duration::from_millis(7); 4]);
+    }
+
+    #[test]
+    fn changes() {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        assert!(close(change_percent(Duration::from_millis(200), Duration::from_millis(250)), 25.0));
+        assert!(close(change_percent(Duration::from_millis(200), Duration::from_millis(150)), -25.0));
+        assert_eq!(change_percent(Duration::ZERO, Duration::from_millis(150)), 0.0);
+    }
+
+    #[test]
+    fn short_durations() {
+        let short = |nanos| Short(Duration::from_nanos(nanos)).to_string();
+        assert_eq!([short(999), short(1_500), short(2_345_678), short(3_210_000_000)],
+                   ["999ns", "1.5µs", "2.35ms", "3.210s"]);
+        assert_eq!(format!("{:>8}", Short(Duration::from_nanos(12))), "    12ns");
+    }
+
+    #[test]
+    fn baseline_lines() {
+        let baseline = Baseline::parse("2020 1 2 300 4000\n2021 25 1 5 6\n").unwrap();
+        assert_eq!(baseline.medians(2020, 1, 2), Some((Duration::from_nanos(300), Duration::from_nanos(4000))));
+        assert_eq!(baseline.medians(2021, 25, 1), Some((Duration::from_nanos(5), Duration::from_nanos(6))));
+        assert_eq!(baseline.medians(2021, 1, 2), None);
+        assert_eq!(baseline.to_string(), "2020 1 2 300 4000\n2021 25 1 5 6\n");
+        for line in ["1 2 300 4000", "2020 1 2 300 4000 5", "2020 -1 2 300 4000", "2020 4294967296 2 300 4000", " "] {
+            let e = Baseline::parse(line).err().unwrap_or_else(|| panic!("'{}' was read", line));
+            assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn saving_keeps_other_solvers() {
+        let path = std::env::temp_dir().join(format!("aoc2020-baseline-{}.txt", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        Baseline::save(2020, &[measurement(3, 1, 1, 2), measurement(1, 1, 3, 4)], &path).unwrap();
+        Baseline::save(2021, &[measurement(1, 1, 5, 6)], &path).unwrap();
+        Baseline::save(2020, &[measurement(3, 1, 7, 8)], &path).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(text, "2020 1 1 3000000 4000000\n2020 3 1 7000000 8000000\n2021 1 1 5000000 6000000\n");
+    }
+
+}
@@ -0,0 +1,279 @@
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use crate::days::{Registration, Registry};
+use crate::report::{self, JsonObject, Timings};
+use crate::runner::{self, RunError};
+use crate::solve::Progress;
+
+pub enum Status {
+    // The answer matched the stored one.
+    Pass(String),
+    Fail { expected: String, actual: String },
+    Error(RunError),
+    // There is no input or no stored answer to check against.
+    Skipped(String),
+    // The solver panicked, with this message.
+    Panicked(String),
+}
+
+pub struct Check {
+    pub day_index: u32,
+    pub part_index: u32,
+    pub title: &'static str,
+    pub status: Status,
+    // All zero when the check was skipped.
+    pub timings: Timings,
+}
+
+impl Check {
+    pub fn regressed(&self) -> bool {
+        matches!(self.status, Status::Fail { .. } | Status::Error(_) | Status::Panicked(_))
+    }
+
+    // Every status has the same fields, which are null where they don't apply.
+    pub fn to_json(&self) -> JsonObject {
+        let object =
+            JsonObject::new()
+                .number("day", self.day_index)
+                .number("part", self.part_index)
+                .string("title", self.title)
+                .null("status")
+                .null("answer")
+                .null("expected")
+                .null("error");
+        let object =
+            match &self.status {
+                Status::Pass(answer) =>
+                    object.string("status", "pass").string("answer", answer).string("expected", answer),
+                Status::Fail { expected, actual } =>
+                    object.string("status", "fail").string("answer", actual).string("expected", expected),
+                Status::Error(e) =>
+                    object.string("status", report::status(e)).object("error", report::error_to_json(e)),
+                Status::Skipped(reason) =>
+                    object.string("status", "skipped").object("error", JsonObject::new().string("message", reason)),
+                Status::Panicked(message) =>
+                    object.string("status", "panicked").object("error", JsonObject::new().string("message", message)),
+            };
+        object.timings(&self.timings)
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Pass(_) => write!(f, "pass"),
+            Status::Fail { expected, actual } => write!(f, "FAIL: expected {}, got {}", expected, actual),
+            Status::Error(e) => write!(f, "ERROR: {}", e),
+            Status::Skipped(reason) => write!(f, "skip: {}", reason),
+            Status::Panicked(message) => write!(f, "PANIC: {}", message),
+        }
+    }
+}
+
+fn check_one(year: u32, registration: &Registration, time_limit: Option<Duration>) -> (Status, Timings) {
+    let input_file_path = runner::input_file_path(year, registration.day_index);
+    let output_file_path = runner::output_file_path(year, registration.day_index, registration.part_index);
+    let input = match std::fs::read_to_string(&input_file_path) {
+        Err(e) => return (Status::Skipped(format!("{}: {}", input_file_path.display(), e)), Timings::default()),
+        Ok(s) => s,
+    };
+    let expected = match std::fs::read_to_string(&output_file_path) {
+        Err(e) => return (Status::Skipped(format!("{}: {}", output_file_path.display(), e)), Timings::default()),
+        Ok(s) => s.trim().to_string(),
+    };
+    let report =
+        match time_limit {
+            None => report::run(registration, &mut input.lines().map(|line| Ok(line.to_string()))),
+            Some(time_limit) => report::run_with_time_limit(registration, input, time_limit, Progress::new()),
+        };
+    let status =
+        match report.result {
+            Err(e) => Status::Error(e),
+            Ok(actual) if actual == expected => Status::Pass(actual),
+            Ok(actual) => Status::Fail { expected, actual },
+        };
+    (status, report.timings)
+}
+
+// Runs check_one, but turns a panic into a status, so one broken solver can't stop the others.
+fn check_isolated(year: u32, registration: &Registration, time_limit: Option<Duration>) -> Check {
+    let (status, timings) =
+        match panic::catch_unwind(AssertUnwindSafe(|| check_one(year, registration, time_limit))) {
+            Ok(outcome) => outcome,
+            Err(payload) => {
+                let message =
+                    match payload.downcast_ref::<&str>() {
+                        Some(s) => s.to_string(),
+                        None => payload.downcast_ref::<String>().cloned().unwrap_or_else(|| "unknown cause".to_string()),
+                    };
+                (Status::Panicked(message), Timings::default())
+            },
+        };
+    Check {
+        day_index: registration.day_index,
+        part_index: registration.part_index,
+        title: registration.title,
+        status,
+        timings,
+    }
+}
+
+// Runs every solver of the registry on its conventional input, and compares with the stored answer.
+// Up to job_count solvers run at once, but the checks always come back in registration order.
+// With a time limit, a solver that takes longer is cancelled, and its check is an error.
+// The progress counts the finished checks.
+pub fn check_all(registry: &Registry, job_count: usize, time_limit: Option<Duration>, progress: &Progress) -> Vec<Check> {
+    let registrations = registry.registrations();
+    progress.start(Some(registrations.len() as u64), "solvers checked");
+    let next_index = AtomicUsize::new(0);
+    let checks: Mutex<Vec<Option<Check>>> = Mutex::new(registrations.iter().map(|_| None).collect());
+    std::thread::scope(|scope| {
+        for _ in 0..job_count.clamp(1, registrations.len().max(1)) {
+            scope.spawn(|| {
+                loop {
+                    let index = next_index.fetch_add(1, Ordering::Relaxed);
+                    let registration =
+                        match registrations.get(index) {
+                            None => break,
+                            Some(r) => r,
+                        };
+                    let check = check_isolated(registry.year(), registration, time_limit);
+                    checks.lock().unwrap()[index] = Some(check);
+                    progress.advance(1);
+                }
+            });
+        }
+    });
+    checks.into_inner().unwrap().into_iter().map(|check| check.unwrap()).collect()
+}
+
+// One job per CPU, or just one if that can't be found out.
+pub fn available_job_count() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::days::{stubs, YEAR};
+    use crate::solve::SolveError;
+
+    fn day_and_part(check: &Check) -> (u32, u32) {
+        (check.day_index, check.part_index)
+    }
+
+    #[test]
+    fn a_panic_only_fails_its_own_check() {
+        let mut registry = Registry::empty(YEAR);
+        registry.add(stubs::registration(1, 1, stubs::panicking));
+        registry.add(*Registry::new().find(1, 2).unwrap());
+        let checks = check_all(&registry, 2, None, &Progress::new());
+        assert!(matches!(&checks[0].status, Status::Panicked(message) if message == "the stub panicked"));
+        assert!(checks[0].regressed());
+        assert!(matches!(checks[1].status, Status::Pass(_)), "{}", checks[1].status);
+    }
+
+    // The first solvers take the longest, so with several jobs they're done last.
+    #[test]
+    fn checks_come_back_in_registration_order() {
+        let mut registry = Registry::empty(YEAR);
+        registry.add(stubs::registration(1, 1, stubs::sleeping::<80>));
+        registry.add(stubs::registration(1, 2, stubs::sleeping::<60>));
+        registry.add(stubs::registration(2, 1, stubs::sleeping::<40>));
+        registry.add(stubs::registration(2, 2, stubs::sleeping::<20>));
+        registry.add(stubs::registration(3, 1, stubs::sleeping::<0>));
+        let progress = Progress::new();
+        let checks = check_all(&registry, 4, None, &progress);
+        let order: Vec<(u32, u32)> = checks.iter().map(day_and_part).collect();
+        assert_eq!(order, [(1, 1), (1, 2), (2, 1), (2, 2), (3, 1)]);
+        let answers: Vec<&str> =
+            checks.iter().map(|check| match &check.status {
+                Status::Fail { actual, .. } => actual.as_str(),
+                status => panic!("day {} part {}: {}", check.day_index, check.part_index, status),
+            }).collect();
+        assert_eq!(answers, ["80", "60", "40", "20", "0"]);
+        assert_eq!(progress.get().map(|(done, total, _)| (done, total)), Some((5, Some(5))));
+    }
+
+    #[test]
+    fn a_solver_out_of_time_is_an_error() {
+        let mut registry = Registry::empty(YEAR);
+        registry.add(stubs::registration(1, 1, stubs::never_finishing));
+        registry.add(stubs::registration(1, 2, stubs::sleeping::<0>));
+        let checks = check_all(&registry, 2, Some(Duration::from_millis(50)), &Progress::new());
+        let cancelled = matches!(checks[0].status, Status::Error(RunError::Solve(SolveError::Cancelled(_))));
+        assert!(cancelled, "{}", checks[0].status);
+        assert!(checks[0].regressed());
+        assert!(matches!(checks[1].status, Status::Fail { .. }));
+    }
+
+    // The names of the fields of a flat object, which is all these tests need.
+    fn field_names(json: &str) -> Vec<String> {
+        let mut names = Vec::new();
+        let mut depth = 0;
+        let mut in_string = false;
+        let mut escaped = false;
+        let mut string_start = 0;
+        for (index, c) in json.char_indices() {
+            if in_string {
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    '"' => {
+                        in_string = false;
+                        if depth == 1 && json[index + 1..].starts_with(':') {
+                            names.push(json[string_start..index].to_string());
+                        }
+                    },
+                    _ => (),
+                }
+                continue;
+            }
+            match c {
+                '"' => {
+                    in_string = true;
+                    string_start = index + 1;
+                },
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => (),
+            }
+        }
+        names
+    }
+
+    #[test]
+    fn every_status_has_the_same_fields() {
+        let statuses = vec![
+            Status::Pass("42".to_string()),
+            Status::Fail { expected: "42".to_string(), actual: "41".to_string() },
+            Status::Error(RunError::Solve(SolveError::NoSolution("no pair adds up".to_string()))),
+            Status::Skipped("no input".to_string()),
+            Status::Panicked("oops".to_string()),
+        ];
+        let objects: Vec<String> =
+            statuses.into_iter().map(|status| {
+                let check = Check { day_index: 1, part_index: 2, title: "Title", status, timings: Timings::default() };
+                check.to_json().to_string()
+            }).collect();
+        for object in &objects {
+            assert_eq!(field_names(object), field_names(&objects[0]), "{}", object);
+        }
+        assert!(objects[0].contains(r#""status":"pass","answer":"42","expected":"42","error":null"#), "{}", objects[0]);
+        assert!(objects[3].contains(r#""answer":null,"expected":null,"error":{"message":"no input"}"#), "{}", objects[3]);
+    }
+
+    #[test]
+    fn a_missing_input_is_skipped() {
+        let mut registry = Registry::empty(YEAR);
+        registry.add(stubs::registration(24, 1, stubs::panicking));
+        let checks = check_all(&registry, 1, None, &Progress::new());
+        assert!(matches!(checks[0].status, Status::Skipped(_)));
+        assert!(!checks[0].regressed());
+    }
+
+}
@@ -1,93 +1,167 @@
-use super::CumulativeSolver;
-
-pub mod part_1 {
-
-    pub struct State {
-        entries: std::vec::Vec<u32>,
-    }
-
-    impl super::CumulativeSolver for State {
-
-        type Input = u32;
-        type Output = u32;
-
-        fn deserialize(&self, line: &std::string::String) -> Option<u32> {
-            line.parse::<u32>().ok()
-        }
-
-        fn serialize(&self, output: &u32) -> std::string::String {
-            output.to_string()
-        }
-
-        fn new() -> Self {
-            State {
-                entries: std::vec::Vec::new(),
-            }
-        }
-
-        fn accumulate(&mut self, entry: u32) -> Option<u32> {
-            self.entries.push(entry);
-            None
-        }
-
-        fn solve(&mut self) -> Option<u32> {
-            for a in &self.entries {
-                for b in &self.entries {
-                    if a + b == 2020 {
-                        return Some(a * b);
-                    }
-                }
-            }
-            return None;
-        }
-
-    }
-
-}
-
-pub mod part_2 {
-
-    pub struct State {
-        entries: std::vec::Vec<u32>,
-    }
-
-    impl super::CumulativeSolver for State {
-
-        type Input = u32;
-        type Output = u32;
-
-        fn deserialize(&self, line: &std::string::String) -> Option<u32> {
-            line.parse::<u32>().ok()
-        }
-
-        fn serialize(&self, output: &u32) -> std::string::String {
-            output.to_string()
-        }
-
-        fn new() -> Self {
-            State {
-                entries: std::vec::Vec::new(),
-            }
-        }
-
-        fn accumulate(&mut self, entry: u32) -> Option<u32> {
-            self.entries.push(entry);
-            None
-        }
-
-        fn solve(&mut self) -> Option<u32> {
-            for a in &self.entries {
-                for b in &self.entries {
-                    for c in &self.entries {
-                        if a + b + c  == 2020 {
-                            return Some(a * b * c);
-                        }
-                    }
-                }
-            }
-            return None;
-        }
-
-    }
-
-}
+use super::CumulativeSolver;
+use super::{Cumulative, CumulativeDay, DayRegistration, Registration, Registry, SolverKind};
+use crate::parse::ParseError;
+use crate::solve::SolveError;
+
+const TITLE: &str = "Report Repair";
+
+fn deserialize_entry(line: &str) -> Result<u32, ParseError> {
+    line.parse::<u32>().map_err(|_| ParseError::new(line, 0..line.len(), "an expense entry"))
+}
+
+pub mod part_1 {
+
+    use super::*;
+
+    pub struct State {
+        entries: std::vec::Vec<u32>,
+    }
+
+    impl super::CumulativeSolver for State {
+
+        type Input = u32;
+        type Output = u32;
+
+        fn deserialize(&self, line: &str) -> Result<u32, ParseError> {
+            deserialize_entry(line)
+        }
+
+        fn serialize(&self, output: &u32) -> std::string::String {
+            output.to_string()
+        }
+
+        fn new() -> Self {
+            State {
+                entries: std::vec::Vec::new(),
+            }
+        }
+
+        fn accumulate(&mut self, entry: u32) -> Option<u32> {
+            self.entries.push(entry);
+            None
+        }
+
+        fn solve(&mut self) -> Result<u32, SolveError> {
+            for a in &self.entries {
+                for b in &self.entries {
+                    // Entries can be large enough for the sum to overflow.
+                    if a.checked_add(*b) == Some(2020) {
+                        return Ok(a * b);
+                    }
+                }
+            }
+            Err(SolveError::NoSolution("no two entries sum to 2020".to_string()))
+        }
+
+    }
+
+}
+
+pub mod part_2 {
+
+    use super::*;
+
+    pub struct State {
+        entries: std::vec::Vec<u32>,
+    }
+
+    impl super::CumulativeSolver for State {
+
+        type Input = u32;
+        type Output = u32;
+
+        fn deserialize(&self, line: &str) -> Result<u32, ParseError> {
+            deserialize_entry(line)
+        }
+
+        fn serialize(&self, output: &u32) -> std::string::String {
+            output.to_string()
+        }
+
+        fn new() -> Self {
+            State {
+                entries: std::vec::Vec::new(),
+            }
+        }
+
+        fn accumulate(&mut self, entry: u32) -> Option<u32> {
+            self.entries.push(entry);
+            None
+        }
+
+        fn solve(&mut self) -> Result<u32, SolveError> {
+            for a in &self.entries {
+                for b in &self.entries {
+                    for c in &self.entries {
+                        if a.checked_add(*b).and_then(|sum| sum.checked_add(*c)) == Some(2020) {
+                            return Ok(a * b * c);
+                        }
+                    }
+                }
+            }
+            Err(SolveError::NoSolution("no three entries sum to 2020".to_string()))
+        }
+
+    }
+
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.add(
+        Registration {
+            day_index: 1,
+            part_index: 1,
+            title: TITLE,
+            kind: SolverKind::Cumulative,
+            solver: || Box::new(Cumulative::<part_1::State>::new()),
+        }
+    );
+    registry.add(
+        Registration {
+            day_index: 1,
+            part_index: 2,
+            title: TITLE,
+            kind: SolverKind::Cumulative,
+            solver: || Box::new(Cumulative::<part_2::State>::new()),
+        }
+    );
+    registry.add_day(
+        DayRegistration {
+            day_index: 1,
+            title: TITLE,
+            solver: || Box::new(CumulativeDay::<part_1::State, part_2::State>::new()),
+        }
+    );
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::days::{solve_day_example, solve_example};
+
+    // The example from the puzzle.
+    const EXAMPLE: &str = "\
+1721
+979
+366
+299
+675
+1456";
+
+    #[test]
+    fn part_1_example() {
+        assert_eq!(solve_example(Cumulative::<part_1::State>::new(), EXAMPLE), "514579");
+    }
+
+    #[test]
+    fn part_2_example() {
+        assert_eq!(solve_example(Cumulative::<part_2::State>::new(), EXAMPLE), "241861950");
+    }
+
+    #[test]
+    fn both_parts_example() {
+        assert_eq!(solve_day_example(CumulativeDay::<part_1::State, part_2::State>::new(), EXAMPLE), ["514579", "241861950"]);
+    }
+
+}
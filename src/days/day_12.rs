@@ -1,151 +1,199 @@
-use super::CumulativeSolver;
-
-pub enum Angle {
-    Clockwise90,
-    CounterClockwise90,
-    Flip, // 180
-}
-
-enum Heading {
-    East,
-    North,
-    West,
-    South,
-}
-
-pub enum Instruction {
-    North(i32),
-    South(i32),
-    East(i32),
-    West(i32),
-    Turn(Angle),
-    Forward(i32),
-}
-
-pub mod part_1 {
-
-    use super::*;
-
-    pub struct State {
-        heading: Heading,
-        position: (i32, i32),
-    }
-
-    fn go(instruction: &Instruction, state: &mut State) {
-        match instruction {
-            Instruction::West(d) => state.position.0 -= d,
-            Instruction::East(d) => state.position.0 += d,
-            Instruction::North(d) => state.position.1 += d,
-            Instruction::South(d) => state.position.1 -= d,
-            Instruction::Forward(d) => {
-                match state.heading {
-                    Heading::East => go(&Instruction::East(*d), state),
-                    Heading::North => go(&Instruction::North(*d), state),
-                    Heading::West => go(&Instruction::West(*d), state),
-                    Heading::South => go(&Instruction::South(*d), state),
-                }
-            },
-            Instruction::Turn(a) => {
-                state.heading =
-                    match a {
-                        Angle::Flip => {
-                            match state.heading {
-                                Heading::East => Heading::West,
-                                Heading::West => Heading::East,
-                                Heading::North => Heading::South,
-                                Heading::South => Heading::North,
-                            }
-                        },
-                        Angle::Clockwise90 => {
-                            match state.heading {
-                                Heading::East => Heading::South,
-                                Heading::West => Heading::North,
-                                Heading::North => Heading::East,
-                                Heading::South => Heading::West,
-                            }
-                        },
-                        Angle::CounterClockwise90 => {
-                            match state.heading {
-                                Heading::East => Heading::North,
-                                Heading::West => Heading::South,
-                                Heading::North => Heading::West,
-                                Heading::South => Heading::East,
-                            }
-                        }
-                    };
-            },
-        }
-    }
-
-    impl super::CumulativeSolver for State {
-
-        type Input = Instruction;
-        type Output = u32;
-
-        fn deserialize(&self, line: &std::string::String) -> Option<Instruction> {
-            let value = &line[1..].parse::<i32>().ok()?;
-            match line.chars().nth(0)? {
-                'N' => {
-                    Some(Instruction::North(*value))
-                },
-                'S' => {
-                    Some(Instruction::South(*value))
-                },
-                'E' => {
-                    Some(Instruction::East(*value))
-                },
-                'W' => {
-                    Some(Instruction::West(*value))
-                },
-                'L' => {
-                    let angle =
-                        match value {
-                            90 => Some(Angle::CounterClockwise90),
-                            270 => Some(Angle::Clockwise90),
-                            180 => Some(Angle::Flip),
-                            _ => None,
-                        };
-                    Some(Instruction::Turn(angle?))
-                },
-                'R' => {
-                    let angle =
-                        match value {
-                            90 => Some(Angle::Clockwise90),
-                            270 => Some(Angle::CounterClockwise90),
-                            180 => Some(Angle::Flip),
-                            _ => None,
-                        };
-                    Some(Instruction::Turn(angle?))
-                },
-                'F' => {
-                    Some(Instruction::Forward(*value))
-                },
-                _ => {
-                    None
-                },
-            }
-        }
-
-        fn serialize(&self, output: &u32) -> std::string::String {
-            output.to_string()
-        }
-
-        fn new() -> Self {
-            State {
-                position: (0, 0),
-                heading: Heading::East,
-            }
-        }
-
-        fn accumulate(&mut self, instruction: Instruction) -> Option<u32> {
-            go(&instruction, self);
-            None
-        }
-
-        fn solve(&mut self) -> Option<u32> {
-            Some((self.position.0.abs() + self.position.1.abs()) as u32)
-        }
-
-    }
-
-}
+use super::CumulativeSolver;
+use super::{Cumulative, Registration, Registry, SolverKind};
+use crate::solve::SolveError;
+use crate::parse::ParseError;
+
+const TITLE: &str = "Rain Risk";
+
+pub enum Angle {
+    Clockwise90,
+    CounterClockwise90,
+    Flip, // 180
+}
+
+enum Heading {
+    East,
+    North,
+    West,
+    South,
+}
+
+pub enum Instruction {
+    North(i32),
+    South(i32),
+    East(i32),
+    West(i32),
+    Turn(Angle),
+    Forward(i32),
+}
+
+pub mod part_1 {
+
+    use super::*;
+
+    pub struct State {
+        heading: Heading,
+        // Wide enough that no input of a sensible length can overflow it.
+        position: (i64, i64),
+    }
+
+    fn go(instruction: &Instruction, state: &mut State) {
+        match instruction {
+            Instruction::West(d) => state.position.0 -= i64::from(*d),
+            Instruction::East(d) => state.position.0 += i64::from(*d),
+            Instruction::North(d) => state.position.1 += i64::from(*d),
+            Instruction::South(d) => state.position.1 -= i64::from(*d),
+            Instruction::Forward(d) => {
+                match state.heading {
+                    Heading::East => go(&Instruction::East(*d), state),
+                    Heading::North => go(&Instruction::North(*d), state),
+                    Heading::West => go(&Instruction::West(*d), state),
+                    Heading::South => go(&Instruction::South(*d), state),
+                }
+            },
+            Instruction::Turn(a) => {
+                state.heading =
+                    match a {
+                        Angle::Flip => {
+                            match state.heading {
+                                Heading::East => Heading::West,
+                                Heading::West => Heading::East,
+                                Heading::North => Heading::South,
+                                Heading::South => Heading::North,
+                            }
+                        },
+                        Angle::Clockwise90 => {
+                            match state.heading {
+                                Heading::East => Heading::South,
+                                Heading::West => Heading::North,
+                                Heading::North => Heading::East,
+                                Heading::South => Heading::West,
+                            }
+                        },
+                        Angle::CounterClockwise90 => {
+                            match state.heading {
+                                Heading::East => Heading::North,
+                                Heading::West => Heading::South,
+                                Heading::North => Heading::West,
+                                Heading::South => Heading::East,
+                            }
+                        }
+                    };
+            },
+        }
+    }
+
+    impl super::CumulativeSolver for State {
+
+        type Input = Instruction;
+        type Output = u64;
+
+        fn deserialize(&self, line: &str) -> Result<Instruction, ParseError> {
+            let action =
+                line.chars().next().ok_or_else(|| {
+                    ParseError::new(line, 0..0, "an action")
+                })?;
+            let value_columns = action.len_utf8()..line.len();
+            let value =
+                &line[value_columns.clone()].parse::<i32>().map_err(|_| {
+                    ParseError::new(line, value_columns.clone(), "a number")
+                })?;
+            let angle_error = || ParseError::new(line, value_columns.clone(), "an angle of 90, 180 or 270");
+            match action {
+                'N' => {
+                    Ok(Instruction::North(*value))
+                },
+                'S' => {
+                    Ok(Instruction::South(*value))
+                },
+                'E' => {
+                    Ok(Instruction::East(*value))
+                },
+                'W' => {
+                    Ok(Instruction::West(*value))
+                },
+                'L' => {
+                    let angle =
+                        match value {
+                            90 => Some(Angle::CounterClockwise90),
+                            270 => Some(Angle::Clockwise90),
+                            180 => Some(Angle::Flip),
+                            _ => None,
+                        };
+                    Ok(Instruction::Turn(angle.ok_or_else(angle_error)?))
+                },
+                'R' => {
+                    let angle =
+                        match value {
+                            90 => Some(Angle::Clockwise90),
+                            270 => Some(Angle::CounterClockwise90),
+                            180 => Some(Angle::Flip),
+                            _ => None,
+                        };
+                    Ok(Instruction::Turn(angle.ok_or_else(angle_error)?))
+                },
+                'F' => {
+                    Ok(Instruction::Forward(*value))
+                },
+                _ => {
+                    Err(ParseError::new(line, 0..action.len_utf8(), "one of the actions 'N', 'S', 'E', 'W', 'L', 'R' or 'F'"))
+                },
+            }
+        }
+
+        fn serialize(&self, output: &u64) -> std::string::String {
+            output.to_string()
+        }
+
+        fn new() -> Self {
+            State {
+                position: (0, 0),
+                heading: Heading::East,
+            }
+        }
+
+        fn accumulate(&mut self, instruction: Instruction) -> Option<u64> {
+            go(&instruction, self);
+            None
+        }
+
+        fn solve(&mut self) -> Result<u64, SolveError> {
+            Ok(self.position.0.unsigned_abs() + self.position.1.unsigned_abs())
+        }
+
+    }
+
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.add(
+        Registration {
+            day_index: 12,
+            part_index: 1,
+            title: TITLE,
+            kind: SolverKind::Cumulative,
+            solver: || Box::new(Cumulative::<part_1::State>::new()),
+        }
+    );
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::days::solve_example;
+
+    // The example from the puzzle.
+    const EXAMPLE: &str = "\
+F10
+N3
+F7
+R90
+F11";
+
+    #[test]
+    fn part_1_example() {
+        assert_eq!(solve_example(Cumulative::<part_1::State>::new(), EXAMPLE), "25");
+    }
+
+}
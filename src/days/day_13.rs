@@ -1,51 +1,103 @@
-use super::FixedLineCountSolver;
-
-pub mod part_1 {
-
-    pub struct Instance {
-        earliest_departure_timestamp: u32,
-        in_service_bus_ids: Vec<u32>,
-    }
-
-    impl super::FixedLineCountSolver for Instance {
-
-        fn from_input(lines: &[String]) -> Option<Instance> {
-            if lines.len() != 2 {
-                return None;
-            }
-            let mut ids = Vec::new();
-            for id in lines[1].split(",") {
-                if id == "x" {
-                    continue;
-                }
-                ids.push(id.parse::<u32>().ok()?);
-            }
-            Some(
-                Instance {
-                    earliest_departure_timestamp: lines[0].parse::<u32>().ok()?,
-                    in_service_bus_ids: ids,
-                }
-            )
-        }
-
-        fn solve(&mut self) -> Option<u32> {
-            let waiting_time =
-                |id| {
-                    let r = self.earliest_departure_timestamp % id;
-                    if r == 0 {0} else {id - r}
-                };
-            let mut minimum_waiting_time = u32::MAX;
-            let mut minimum_wait_id = 0;
-            for id in &self.in_service_bus_ids {
-                let id_waiting_time = waiting_time(id);
-                if id_waiting_time < minimum_waiting_time {
-                    minimum_waiting_time = id_waiting_time;
-                    minimum_wait_id = *id;
-                }
-            }
-            Some(minimum_wait_id * minimum_waiting_time)
-        }
-
-    }
-
-}
+use super::FixedLineCountSolver;
+use super::{FixedLineCount, Registration, Registry, SolverKind};
+
+const TITLE: &str = "Shuttle Search";
+
+pub mod part_1 {
+
+    use crate::math;
+    use crate::parse::{self, ParseError};
+    use crate::solve::SolveError;
+
+    pub struct Instance {
+        earliest_departure_timestamp: u32,
+        in_service_bus_ids: Vec<u32>,
+    }
+
+    impl super::FixedLineCountSolver for Instance {
+
+        type Output = u32;
+
+        fn from_input(lines: &[String]) -> Result<Instance, ParseError> {
+            parse::expect_line_count(lines, 2)?;
+            let mut ids = Vec::new();
+            for (offset, id) in parse::split_with_offsets(&lines[1], ',') {
+                if id == "x" {
+                    continue;
+                }
+                // An ID is how often the bus departs, so it can't be 0.
+                ids.push(id.parse::<u32>().ok().filter(|id| *id > 0).ok_or_else(|| {
+                    ParseError::new(&lines[1], offset..(offset + id.len()), "a bus ID above 0 or 'x'").on_line(1)
+                })?);
+            }
+            let earliest_departure_timestamp =
+                lines[0].parse::<u32>().map_err(|_| {
+                    ParseError::new(&lines[0], 0..lines[0].len(), "a timestamp")
+                })?;
+            Ok(
+                Instance {
+                    earliest_departure_timestamp,
+                    in_service_bus_ids: ids,
+                }
+            )
+        }
+
+        fn solve(&mut self) -> Result<u32, SolveError> {
+            if self.in_service_bus_ids.is_empty() {
+                return Err(SolveError::PreconditionViolated("there are no buses in service".to_string()));
+            }
+            // Buses leave at multiples of their id, so the wait takes the timestamp up to the next one.
+            let waiting_time =
+                |id: &u32| {
+                    // Both are u32, so the wait is below the id and fits back in 32 bits.
+                    math::checked_neg_mod(u128::from(self.earliest_departure_timestamp), u128::from(*id))
+                        .map(|wait| wait as u32)
+                        .ok_or_else(|| SolveError::PreconditionViolated("a bus ID is 0".to_string()))
+                };
+            let mut minimum_waiting_time = u32::MAX;
+            let mut minimum_wait_id = 0;
+            for id in &self.in_service_bus_ids {
+                let id_waiting_time = waiting_time(id)?;
+                if id_waiting_time < minimum_waiting_time {
+                    minimum_waiting_time = id_waiting_time;
+                    minimum_wait_id = *id;
+                }
+            }
+            minimum_wait_id.checked_mul(minimum_waiting_time).ok_or_else(|| {
+                SolveError::LimitExceeded("the answer overflows 32 bits".to_string())
+            })
+        }
+
+    }
+
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.add(
+        Registration {
+            day_index: 13,
+            part_index: 1,
+            title: TITLE,
+            kind: SolverKind::FixedLineCount,
+            solver: || Box::new(FixedLineCount::<part_1::Instance>::new()),
+        }
+    );
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::days::solve_example;
+
+    // The example from the puzzle.
+    const EXAMPLE: &str = "\
+939
+7,13,x,x,59,x,31,19";
+
+    #[test]
+    fn part_1_example() {
+        assert_eq!(solve_example(FixedLineCount::<part_1::Instance>::new(), EXAMPLE), "295");
+    }
+
+}
@@ -1,84 +1,214 @@
-use super::FixedLineCountSolver;
-use std::collections::HashMap;
-
-fn from_input(lines: &[String]) -> Option<Vec<u32>> {
-    if lines.len() != 1 {
-        return None;
-    }
-    let line = &lines[0];
-    let mut numbers = Vec::new();
-    for part in line.split(",") {
-        numbers.push(part.parse::<u32>().ok()?)
-    }
-    Some(numbers)
-}
-
-fn solve_limit(final_index: u32, numbers: &[u32]) -> Option<u32> {
-    if numbers.is_empty() {
-        return None;
-    }
-    let mut turn_seen: HashMap<u32, u32> = HashMap::new();
-    let mut turn_index = 1;
-    let mut last_number = numbers[0];
-    for number in &numbers[1..] {
-        turn_seen.insert(last_number, turn_index);
-        last_number = *number;
-        turn_index += 1;
-    }
-    for turn_index in turn_index..final_index {
-        let number = {
-            match turn_seen.get(&last_number) {
-                None => 0,
-                Some(i) => turn_index - i,
-            }
-        };
-        turn_seen.insert(last_number, turn_index);
-        last_number = number;
-    }
-    Some(last_number)
-}
-
-
-pub mod part_1 {
-
-    use super::*;
-
-    pub struct Instance {
-        numbers: Vec<u32>,
-    }
-
-    impl super::FixedLineCountSolver for Instance {
-
-        fn from_input(lines: &[String]) -> Option<Instance> {
-            Some(Instance {numbers: from_input(lines)?})
-        }
-
-        fn solve(&mut self) -> Option<u32> {
-            solve_limit(2020, &self.numbers)
-        }
-
-    }
-
-}
-
-pub mod part_2 {
-
-    use super::*;
-
-    pub struct Instance {
-        numbers: Vec<u32>,
-    }
-
-    impl super::FixedLineCountSolver for Instance {
-
-        fn from_input(lines: &[String]) -> Option<Instance> {
-            Some(Instance {numbers: from_input(lines)?})
-        }
-
-        fn solve(&mut self) -> Option<u32> {
-            solve_limit(30000000, &self.numbers)
-        }
-
-    }
-
-}
+use super::FixedLineCountSolver;
+use super::{DayRegistration, FixedLineCount, FixedLineCountDay, Registration, Registry, SolverKind};
+use crate::parse::{self, ParseError};
+use crate::solve::{Cancellation, Progress, SolveError};
+use std::collections::HashMap;
+
+const TITLE: &str = "Rambunctious Recitation";
+
+fn from_input(lines: &[String]) -> Result<Vec<u32>, ParseError> {
+    parse::expect_line_count(lines, 1)?;
+    let line = &lines[0];
+    let mut numbers = Vec::new();
+    for (offset, part) in parse::split_with_offsets(line, ',') {
+        numbers.push(part.parse::<u32>().map_err(|_| {
+            ParseError::new(line, offset..(offset + part.len()), "a starting number")
+        })?)
+    }
+    Ok(numbers)
+}
+
+// How many turns go by between checks for cancellation, and updates of progress.
+const TURNS_PER_POLL: u32 = 1 << 16;
+
+fn solve_limit(final_index: u32, numbers: &[u32], cancellation: &Cancellation, progress: &Progress)
+               -> Result<u32, SolveError> {
+    if numbers.is_empty() {
+        return Err(SolveError::PreconditionViolated("there are no starting numbers".to_string()));
+    }
+    progress.start(Some(final_index as u64), "turns");
+    let mut turn_seen: HashMap<u32, u32> = HashMap::new();
+    let mut turn_index = 1;
+    let mut last_number = numbers[0];
+    for number in &numbers[1..] {
+        turn_seen.insert(last_number, turn_index);
+        last_number = *number;
+        turn_index += 1;
+    }
+    for turn_index in turn_index..final_index {
+        if turn_index % TURNS_PER_POLL == 0 {
+            cancellation.check()?;
+            progress.set(turn_index as u64);
+        }
+        let number = {
+            match turn_seen.get(&last_number) {
+                None => 0,
+                Some(i) => turn_index - i,
+            }
+        };
+        turn_seen.insert(last_number, turn_index);
+        last_number = number;
+    }
+    Ok(last_number)
+}
+
+
+pub mod part_1 {
+
+    use super::*;
+
+    pub struct Instance {
+        pub(super) numbers: Vec<u32>,
+        cancellation: Cancellation,
+        progress: Progress,
+    }
+
+    impl super::FixedLineCountSolver for Instance {
+
+        type Output = u32;
+
+        fn from_input(lines: &[String]) -> Result<Instance, ParseError> {
+            Ok(
+                Instance {
+                    numbers: from_input(lines)?,
+                    cancellation: Cancellation::new(),
+                    progress: Progress::new(),
+                }
+            )
+        }
+
+        fn solve(&mut self) -> Result<u32, SolveError> {
+            solve_limit(2020, &self.numbers, &self.cancellation, &self.progress)
+        }
+
+        fn set_cancellation(&mut self, cancellation: Cancellation) {
+            self.cancellation = cancellation;
+        }
+
+        fn set_progress(&mut self, progress: Progress) {
+            self.progress = progress;
+        }
+
+    }
+
+}
+
+pub mod part_2 {
+
+    use super::*;
+
+    pub struct Instance {
+        numbers: Vec<u32>,
+        cancellation: Cancellation,
+        progress: Progress,
+    }
+
+    impl super::FixedLineCountSolver for Instance {
+
+        type Output = u32;
+
+        fn from_input(lines: &[String]) -> Result<Instance, ParseError> {
+            Ok(
+                Instance {
+                    numbers: from_input(lines)?,
+                    cancellation: Cancellation::new(),
+                    progress: Progress::new(),
+                }
+            )
+        }
+
+        fn solve(&mut self) -> Result<u32, SolveError> {
+            solve_limit(30000000, &self.numbers, &self.cancellation, &self.progress)
+        }
+
+        fn set_cancellation(&mut self, cancellation: Cancellation) {
+            self.cancellation = cancellation;
+        }
+
+        fn set_progress(&mut self, progress: Progress) {
+            self.progress = progress;
+        }
+
+    }
+
+    // Both parts start from the same numbers.
+    impl From<&super::part_1::Instance> for Instance {
+        fn from(part_1: &super::part_1::Instance) -> Instance {
+            Instance {
+                numbers: part_1.numbers.clone(),
+                cancellation: Cancellation::new(),
+                progress: Progress::new(),
+            }
+        }
+    }
+
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.add(
+        Registration {
+            day_index: 15,
+            part_index: 1,
+            title: TITLE,
+            kind: SolverKind::FixedLineCount,
+            solver: || Box::new(FixedLineCount::<part_1::Instance>::new()),
+        }
+    );
+    registry.add(
+        Registration {
+            day_index: 15,
+            part_index: 2,
+            title: TITLE,
+            kind: SolverKind::FixedLineCount,
+            solver: || Box::new(FixedLineCount::<part_2::Instance>::new()),
+        }
+    );
+    registry.add_day(
+        DayRegistration {
+            day_index: 15,
+            title: TITLE,
+            solver: || Box::new(FixedLineCountDay::<part_1::Instance, part_2::Instance>::new()),
+        }
+    );
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::days::{solve_day_example, solve_example};
+
+    // The examples from the puzzle, with the answers to both parts.
+    const EXAMPLES: [(&str, &str, &str); 7] = [("0,3,6", "436", "175594"),
+                                               ("1,3,2", "1", "2578"),
+                                               ("2,1,3", "10", "3544142"),
+                                               ("1,2,3", "27", "261214"),
+                                               ("2,3,1", "78", "6895259"),
+                                               ("3,2,1", "438", "18"),
+                                               ("3,1,2", "1836", "362")];
+
+    #[test]
+    fn part_1_examples() {
+        for (input, answer, _) in EXAMPLES.iter() {
+            assert_eq!(solve_example(FixedLineCount::<part_1::Instance>::new(), input), *answer, "{}", input);
+        }
+    }
+
+    // Each example takes 30 million turns, so these only run with --ignored.
+    #[test]
+    #[ignore]
+    fn part_2_examples() {
+        for (input, _, answer) in EXAMPLES.iter() {
+            assert_eq!(solve_example(FixedLineCount::<part_2::Instance>::new(), input), *answer, "{}", input);
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn both_parts_example() {
+        let (input, answer_1, answer_2) = EXAMPLES[0];
+        assert_eq!(solve_day_example(FixedLineCountDay::<part_1::Instance, part_2::Instance>::new(), input),
+                   [answer_1, answer_2]);
+    }
+
+}
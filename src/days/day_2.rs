@@ -1,140 +1,285 @@
-use super::CumulativeSolver;
-use regex::Regex;
-
-pub struct Range {
-    offset: u32,
-    length: u32,
-}
-
-pub struct Entry {
-    range: Range,
-    character: char,
-    password: std::string::String,
-}
-
-fn deserialize_entry(expression: &regex::Regex, line: &std::string::String) -> Option<Entry> {
-    let captures = expression.captures(line)?;
-    let min = captures.get(1)?.as_str().parse::<u32>().ok()?;
-    let max = captures.get(2)?.as_str().parse::<u32>().ok()?;
-    let character = captures.get(3)?.as_str().chars().nth(0)?;
-    let password = captures.get(4)?.as_str();
-    Some(
-        Entry {
-            range: Range {
-                offset: min,
-                length: max - min,
-            },
-            character: character,
-            password: password.to_string()
-        }
-    )
-}
-
-
-pub mod part_1 {
-
-    use super::*;
-
-    pub struct State {
-        valid_password_count: u32,
-        input_expression: Regex,
-    }
-
-    impl super::CumulativeSolver for State {
-
-        type Input = Entry;
-        type Output = u32;
-
-        fn deserialize(&self, line: &std::string::String) -> Option<Entry> {
-            deserialize_entry(&self.input_expression, line)
-        }
-
-        fn serialize(&self, output: &u32) -> std::string::String {
-            output.to_string()
-        }
-
-        fn new() -> Self {
-            State {
-                valid_password_count: 0,
-                input_expression: Regex::new(r"^(\d+)-(\d+) ([a-z]): ([a-z]+)$").unwrap(),
-            }
-        }
-
-        fn accumulate(&mut self, entry: Entry) -> Option<u32> {
-            let min = entry.range.offset;
-            let max = entry.range.offset + entry.range.length;
-            let mut count = 0;
-            for c in entry.password.chars() {
-                if c == entry.character {
-                    count += 1;
-                    if count > max {
-                        return None;
-                    }
-                }
-            }
-            if count >= min {
-                self.valid_password_count += 1;
-            }
-            None
-        }
-
-        fn solve(&mut self) -> Option<u32> {
-            return Some(self.valid_password_count);
-        }
-
-    }
-
-}
-
-pub mod part_2 {
-
-    use super::*;
-
-    pub struct State {
-        valid_password_count: u32,
-        input_expression: Regex,
-    }
-
-    impl super::CumulativeSolver for State {
-
-        type Input = Entry;
-        type Output = u32;
-
-        fn deserialize(&self, line: &std::string::String) -> Option<Entry> {
-            deserialize_entry(&self.input_expression, line)
-        }
-
-        fn serialize(&self, output: &u32) -> std::string::String {
-            output.to_string()
-        }
-
-        fn new() -> Self {
-            State {
-                valid_password_count: 0,
-                input_expression: Regex::new(r"^(\d+)-(\d+) ([a-z]): ([a-z]+)$").unwrap(),
-            }
-        }
-
-        fn accumulate(&mut self, entry: Entry) -> Option<u32> {
-            let index_lo = (entry.range.offset - 1) as usize;
-            let index_hi = (entry.range.offset + entry.range.length - 1) as usize;
-            let characters: Vec<char> = entry.password.chars().collect();
-            if index_hi >= characters.len() {
-                return None;
-            }
-            let char_lo = characters[index_lo];
-            let char_hi = characters[index_hi];
-            let valid = (char_lo == entry.character) != (char_hi == entry.character);
-            if valid {
-                self.valid_password_count += 1;
-            }
-            None
-        }
-
-        fn solve(&mut self) -> Option<u32> {
-            return Some(self.valid_password_count);
-        }
-
-    }
-
-}
+use super::CumulativeSolver;
+use super::{Cumulative, CumulativeDay, DayRegistration, Registration, Registry, SolverKind};
+use crate::parse::ParseError;
+use crate::solve::SolveError;
+use regex::Regex;
+
+const TITLE: &str = "Password Philosophy";
+
+#[derive(Clone)]
+pub struct Range {
+    offset: u32,
+    length: u32,
+}
+
+#[derive(Clone)]
+pub struct Entry {
+    range: Range,
+    character: char,
+    password: std::string::String,
+}
+
+// Counts are ASCII digits only, since \d would also take digits of other scripts, which don't parse as numbers.
+const ENTRY_PATTERN: &str = r"^([0-9]+)-([0-9]+) ([a-z]): ([a-z]+)$";
+
+// The parts of an entry in order, along with what each should look like.
+const ENTRY_PARTS: [(&str, &str); 7] = [(r"^[0-9]+", "a minimum count"),
+                                        (r"^-", "'-'"),
+                                        (r"^[0-9]+", "a maximum count"),
+                                        (r"^ ", "' '"),
+                                        (r"^[a-z]", "a letter from 'a' to 'z'"),
+                                        (r"^: ", "': '"),
+                                        (r"^[a-z]+$", "a password of letters from 'a' to 'z'")];
+
+// The expressions for a whole entry and for each of its parts, compiled once for every solver.
+struct EntryExpressions {
+    entry: Regex,
+    parts: Vec<(Regex, &'static str)>,
+}
+
+impl EntryExpressions {
+    fn new() -> Self {
+        EntryExpressions {
+            entry: Regex::new(ENTRY_PATTERN).unwrap(),
+            parts: ENTRY_PARTS.iter().map(|(pattern, expected)| (Regex::new(pattern).unwrap(), *expected)).collect(),
+        }
+    }
+}
+
+// Finds the first part of a line that doesn't look like it should.
+fn diagnose_entry(expressions: &EntryExpressions, line: &str) -> ParseError {
+    let mut column = 0;
+    for (expression, expected) in expressions.parts.iter() {
+        let rest = &line[column..];
+        match expression.find(rest) {
+            Some(m) => {
+                column += m.end();
+            },
+            None => {
+                let word_length = rest.split(' ').next().unwrap_or("").len();
+                let first_length = rest.chars().next().map_or(0, |c| c.len_utf8());
+                let length = std::cmp::max(word_length, first_length);
+                return ParseError::new(line, column..(column + length), expected);
+            }
+        }
+    }
+    ParseError::new(line, 0..line.len(), "a password policy and a password")
+}
+
+fn deserialize_count(expressions: &EntryExpressions, line: &str, capture: Option<regex::Match>)
+                     -> Result<u32, ParseError> {
+    let capture = capture.ok_or_else(|| diagnose_entry(expressions, line))?;
+    capture.as_str().parse::<u32>().map_err(|_| {
+        ParseError::new(line, capture.range(), "a count that fits in 32 bits")
+    })
+}
+
+fn deserialize_entry(expressions: &EntryExpressions, line: &str) -> Result<Entry, ParseError> {
+    let captures = expressions.entry.captures(line).ok_or_else(|| diagnose_entry(expressions, line))?;
+    let min = deserialize_count(expressions, line, captures.get(1))?;
+    let max = deserialize_count(expressions, line, captures.get(2))?;
+    if max < min {
+        let columns = captures.get(1).unwrap().start()..captures.get(2).unwrap().end();
+        return Err(ParseError::new(line, columns, "a minimum no greater than the maximum"));
+    }
+    let character =
+        captures.get(3).and_then(|c| c.as_str().chars().next()).ok_or_else(|| diagnose_entry(expressions, line))?;
+    let password = captures.get(4).ok_or_else(|| diagnose_entry(expressions, line))?.as_str();
+    Ok(
+        Entry {
+            range: Range {
+                offset: min,
+                length: max - min,
+            },
+            character,
+            password: password.to_string()
+        }
+    )
+}
+
+
+pub mod part_1 {
+
+    use super::*;
+
+    pub struct State {
+        valid_password_count: u32,
+        input_expressions: EntryExpressions,
+    }
+
+    impl super::CumulativeSolver for State {
+
+        type Input = Entry;
+        type Output = u32;
+
+        fn deserialize(&self, line: &str) -> Result<Entry, ParseError> {
+            deserialize_entry(&self.input_expressions, line)
+        }
+
+        fn serialize(&self, output: &u32) -> std::string::String {
+            output.to_string()
+        }
+
+        fn new() -> Self {
+            State {
+                valid_password_count: 0,
+                input_expressions: EntryExpressions::new(),
+            }
+        }
+
+        fn accumulate(&mut self, entry: Entry) -> Option<u32> {
+            let min = entry.range.offset;
+            let max = entry.range.offset + entry.range.length;
+            let mut count = 0;
+            for c in entry.password.chars() {
+                if c == entry.character {
+                    count += 1;
+                    if count > max {
+                        return None;
+                    }
+                }
+            }
+            if count >= min {
+                self.valid_password_count += 1;
+            }
+            None
+        }
+
+        fn solve(&mut self) -> Result<u32, SolveError> {
+            Ok(self.valid_password_count)
+        }
+
+    }
+
+}
+
+pub mod part_2 {
+
+    use super::*;
+
+    pub struct State {
+        valid_password_count: u32,
+        input_expressions: EntryExpressions,
+    }
+
+    impl super::CumulativeSolver for State {
+
+        type Input = Entry;
+        type Output = u32;
+
+        fn deserialize(&self, line: &str) -> Result<Entry, ParseError> {
+            deserialize_entry(&self.input_expressions, line)
+        }
+
+        fn serialize(&self, output: &u32) -> std::string::String {
+            output.to_string()
+        }
+
+        fn new() -> Self {
+            State {
+                valid_password_count: 0,
+                input_expressions: EntryExpressions::new(),
+            }
+        }
+
+        fn accumulate(&mut self, entry: Entry) -> Option<u32> {
+            // Positions count from 1, so a policy naming position 0 can't be met.
+            let index_lo =
+                match entry.range.offset.checked_sub(1) {
+                    None => return None,
+                    Some(index) => index as usize,
+                };
+            let index_hi = (entry.range.offset + entry.range.length - 1) as usize;
+            let characters: Vec<char> = entry.password.chars().collect();
+            if index_hi >= characters.len() {
+                return None;
+            }
+            let char_lo = characters[index_lo];
+            let char_hi = characters[index_hi];
+            let valid = (char_lo == entry.character) != (char_hi == entry.character);
+            if valid {
+                self.valid_password_count += 1;
+            }
+            None
+        }
+
+        fn solve(&mut self) -> Result<u32, SolveError> {
+            Ok(self.valid_password_count)
+        }
+
+    }
+
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.add(
+        Registration {
+            day_index: 2,
+            part_index: 1,
+            title: TITLE,
+            kind: SolverKind::Cumulative,
+            solver: || Box::new(Cumulative::<part_1::State>::new()),
+        }
+    );
+    registry.add(
+        Registration {
+            day_index: 2,
+            part_index: 2,
+            title: TITLE,
+            kind: SolverKind::Cumulative,
+            solver: || Box::new(Cumulative::<part_2::State>::new()),
+        }
+    );
+    registry.add_day(
+        DayRegistration {
+            day_index: 2,
+            title: TITLE,
+            solver: || Box::new(CumulativeDay::<part_1::State, part_2::State>::new()),
+        }
+    );
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::days::{solve_day_example, solve_example};
+
+    // The example from the puzzle.
+    const EXAMPLE: &str = "\
+1-3 a: abcde
+1-3 b: cdefg
+2-9 c: ccccccccc";
+
+    #[test]
+    fn part_1_example() {
+        assert_eq!(solve_example(Cumulative::<part_1::State>::new(), EXAMPLE), "2");
+    }
+
+    #[test]
+    fn part_2_example() {
+        assert_eq!(solve_example(Cumulative::<part_2::State>::new(), EXAMPLE), "1");
+    }
+
+    #[test]
+    fn both_parts_example() {
+        assert_eq!(solve_day_example(CumulativeDay::<part_1::State, part_2::State>::new(), EXAMPLE), ["2", "1"]);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let expressions = EntryExpressions::new();
+        let diagnosis = |line| {
+            let e = deserialize_entry(&expressions, line).err().unwrap();
+            (e.columns, e.expected)
+        };
+        assert_eq!(diagnosis("1-3 a abcde"), (5..6, "': '".to_string()));
+        assert_eq!(diagnosis("1-3 A: abcde"), (4..6, "a letter from 'a' to 'z'".to_string()));
+        assert_eq!(diagnosis("3-1 a: abcde"), (0..3, "a minimum no greater than the maximum".to_string()));
+        assert_eq!(diagnosis("1-99999999999 a: abcde"), (2..13, "a count that fits in 32 bits".to_string()));
+        // Digits of other scripts aren't counts.
+        assert_eq!(diagnosis("\u{661}-3 a: abcde"), (0..4, "a minimum count".to_string()));
+        assert_eq!(diagnosis("1-\u{ff13} a: abcde"), (2..5, "a maximum count".to_string()));
+    }
+
+}
@@ -1,144 +1,187 @@
-use super::CumulativeSolver;
-
-// All rows of the map are 31 bits long
-const ROW_LENGTH: u32 = 31;
-type Row = u32;
-const SLOPE_COUNT: usize = 5;
-const SLOPES: [(u32, u32); SLOPE_COUNT] = [(1, 1),
-                                           (3, 1),
-                                           (5, 1),
-                                           (7, 1),
-                                           (1, 2)];
-
-fn deserialize_row(line: &std::string::String) -> Option<Row> {
-
-    if line.len() != (ROW_LENGTH as usize) {
-        return None;
-    }
-
-    let mut row: u32 = 0;
-    for c in line.chars() {
-        match c {
-            '.' => {
-                row = (row << 1) + 0;
-            },
-            '#' => {
-                row = (row << 1) + 1;
-            },
-            _ => {
-                return None
-            }
-        }
-    }
-
-    Some(row)
-}
-
-
-pub mod part_1 {
-
-    use super::*;
-
-    pub struct State {
-        tree_hit_count: u32,
-        row_index: u32,
-    }
-
-    impl super::CumulativeSolver for State {
-
-        type Input = Row;
-        type Output = u32;
-
-        fn deserialize(&self, line: &std::string::String) -> Option<Row> {
-            deserialize_row(line)
-        }
-
-        fn serialize(&self, output: &u32) -> std::string::String {
-            output.to_string()
-        }
-
-        fn new() -> Self {
-            State {
-                tree_hit_count: 0,
-                row_index: 0,
-            }
-        }
-
-        fn accumulate(&mut self, row: Row) -> Option<u32> {
-            let col_index = (3*self.row_index) % ROW_LENGTH;
-            let col_mask = 1 << (ROW_LENGTH - 1 - col_index);
-            let hit_tree = (col_mask & row) != 0;
-            if hit_tree {
-                self.tree_hit_count += 1;
-            }
-            self.row_index += 1;
-            None
-        }
-
-        fn solve(&mut self) -> Option<u32> {
-            return Some(self.tree_hit_count);
-        }
-
-    }
-
-}
-
-pub mod part_2 {
-
-    use super::*;
-
-    pub struct State {
-        tree_hit_count: [u32; SLOPE_COUNT],
-        row_index: u32,
-    }
-
-    impl super::CumulativeSolver for State {
-
-        type Input = Row;
-        type Output = u32;
-
-        fn deserialize(&self, line: &std::string::String) -> Option<Row> {
-            deserialize_row(line)
-        }
-
-        fn serialize(&self, output: &u32) -> std::string::String {
-            output.to_string()
-        }
-
-        fn new() -> Self {
-            State {
-                tree_hit_count: [0; SLOPE_COUNT],
-                row_index: 0,
-            }
-        }
-
-        fn accumulate(&mut self, row: Row) -> Option<u32> {
-            for (slope_index, slope) in SLOPES.iter().enumerate() {
-                let col_index = (slope.0*(self.row_index/slope.1)) % ROW_LENGTH;
-                let col_mask: u32 = {
-                    if (self.row_index % slope.1) == 0 {
-                        1 << (ROW_LENGTH - 1 - col_index)
-                    } else {
-                        0
-                    }
-                };
-                let hit = (col_mask & row) != 0;
-                if hit {
-                    self.tree_hit_count[slope_index] += 1;
-                }
-            }
-            self.row_index += 1;
-            None
-        }
-
-        fn solve(&mut self) -> Option<u32> {
-            let mut product = 1;
-            for count in self.tree_hit_count.iter() {
-                product *= count;
-            }
-            return Some(product);
-        }
-
-    }
-
-}
+use super::FixedLineCountSolver;
+use super::{DayRegistration, FixedLineCount, FixedLineCountDay, Registration, Registry, SolverKind};
+use crate::grid::{Edge, Grid};
+use crate::parse::ParseError;
+use crate::solve::SolveError;
+
+const TITLE: &str = "Toboggan Trajectory";
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Square {
+    Open,
+    Tree,
+}
+
+// Right and down.
+const SLOPE_COUNT: usize = 5;
+const SLOPES: [(i64, i64); SLOPE_COUNT] = [(1, 1),
+                                           (3, 1),
+                                           (5, 1),
+                                           (7, 1),
+                                           (1, 2)];
+
+fn from_input(lines: &[String]) -> Result<Grid<Square>, ParseError> {
+    Grid::parse(lines, "'.' or '#'", |c| {
+        match c {
+            '.' => Some(Square::Open),
+            '#' => Some(Square::Tree),
+            _ => None,
+        }
+    })
+}
+
+// Goes down from the top left corner to the bottom row, where the map repeats to the right.
+fn tree_hit_count(map: &Grid<Square>, (right, down): (i64, i64)) -> u32 {
+    let mut count = 0;
+    let mut position = (0, 0);
+    while position.1 < map.height() as i64 {
+        if map.get(position, Edge::Wrap) == Some(&Square::Tree) {
+            count += 1;
+        }
+        position = (position.0 + right, position.1 + down);
+    }
+    count
+}
+
+pub mod part_1 {
+
+    use super::*;
+
+    pub struct Instance {
+        pub(super) map: Grid<Square>,
+    }
+
+    impl super::FixedLineCountSolver for Instance {
+
+        type Output = u32;
+
+        fn from_input(lines: &[String]) -> Result<Instance, ParseError> {
+            Ok(
+                Instance {
+                    map: super::from_input(lines)?,
+                }
+            )
+        }
+
+        fn solve(&mut self) -> Result<u32, SolveError> {
+            Ok(tree_hit_count(&self.map, SLOPES[1]))
+        }
+
+    }
+
+}
+
+pub mod part_2 {
+
+    use super::*;
+
+    pub struct Instance {
+        map: Grid<Square>,
+    }
+
+    impl super::FixedLineCountSolver for Instance {
+
+        type Output = u32;
+
+        fn from_input(lines: &[String]) -> Result<Instance, ParseError> {
+            Ok(
+                Instance {
+                    map: super::from_input(lines)?,
+                }
+            )
+        }
+
+        fn solve(&mut self) -> Result<u32, SolveError> {
+            let mut product: u32 = 1;
+            for slope in SLOPES.iter() {
+                product =
+                    product.checked_mul(tree_hit_count(&self.map, *slope)).ok_or_else(|| {
+                        SolveError::LimitExceeded("the product of the tree counts overflows 32 bits".to_string())
+                    })?;
+            }
+            Ok(product)
+        }
+
+    }
+
+    impl From<&super::part_1::Instance> for Instance {
+        fn from(part_1: &super::part_1::Instance) -> Instance {
+            Instance {
+                map: part_1.map.clone(),
+            }
+        }
+    }
+
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.add(
+        Registration {
+            day_index: 3,
+            part_index: 1,
+            title: TITLE,
+            kind: SolverKind::FixedLineCount,
+            solver: || Box::new(FixedLineCount::<part_1::Instance>::new()),
+        }
+    );
+    registry.add(
+        Registration {
+            day_index: 3,
+            part_index: 2,
+            title: TITLE,
+            kind: SolverKind::FixedLineCount,
+            solver: || Box::new(FixedLineCount::<part_2::Instance>::new()),
+        }
+    );
+    registry.add_day(
+        DayRegistration {
+            day_index: 3,
+            title: TITLE,
+            solver: || Box::new(FixedLineCountDay::<part_1::Instance, part_2::Instance>::new()),
+        }
+    );
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::days::{solve_day_example, solve_example};
+
+    // The example from the puzzle.
+    const EXAMPLE: &str = "\
+..##.......
+#...#...#..
+.#....#..#.
+..#.#...#.#
+.#...##..#.
+..#.##.....
+.#.#.#....#
+.#........#
+#.##...#...
+#...##....#
+.#..#...#.#";
+
+    #[test]
+    fn part_1_example() {
+        assert_eq!(solve_example(FixedLineCount::<part_1::Instance>::new(), EXAMPLE), "7");
+    }
+
+    #[test]
+    fn part_2_example() {
+        assert_eq!(solve_example(FixedLineCount::<part_2::Instance>::new(), EXAMPLE), "336");
+    }
+
+    #[test]
+    fn both_parts_example() {
+        assert_eq!(solve_day_example(FixedLineCountDay::<part_1::Instance, part_2::Instance>::new(), EXAMPLE), ["7", "336"]);
+    }
+
+    // Rows used to be kept in 32 bits, so wider maps couldn't be read.
+    #[test]
+    fn wide_map() {
+        let map = format!("{}\n{}#{}\n", ".".repeat(40), ".".repeat(3), ".".repeat(36));
+        assert_eq!(solve_example(FixedLineCount::<part_1::Instance>::new(), &map), "1");
+    }
+
+}
@@ -1,141 +1,199 @@
-use super::CumulativeSolver;
-
-type Questions = u32;
-
-pub enum Row {
-    GroupTerminator,
-    Response(Questions),
-}
-
-const ALL_QUESTIONS: Questions = 0x03ffffff;
-
-pub mod part_1 {
-
-    use super::*;
-
-    pub struct State {
-        questions: u32,
-        yes_count: u32
-    }
-
-    impl super::CumulativeSolver for State {
-
-        type Input = Row;
-        type Output = u32;
-
-        fn deserialize(&self, line: &std::string::String) -> Option<Row> {
-
-            if line.is_empty() {
-                return Some(Row::GroupTerminator);
-            }
-
-            let mut questions: Questions = 0;
-            for c in line.chars() {
-                if c < 'a' || c > 'z' {
-                    return None
-                }
-                let question_index = (c as u32) - ('a' as u32);
-                questions |= 1 << question_index;
-            }
-
-            return Some(Row::Response(questions))
-
-        }
-
-        fn serialize(&self, output: &u32) -> std::string::String {
-            output.to_string()
-        }
-
-        fn new() -> Self {
-            State {
-                questions: 0,
-                yes_count: 0,
-            }
-        }
-
-        fn accumulate(&mut self, row: Row) -> Option<u32> {
-            match row {
-                Row::GroupTerminator => {
-                    self.yes_count += self.questions.count_ones();
-                    self.questions = 0;
-                },
-                Row::Response(questions) => {
-                    self.questions |= questions;
-                },
-            }
-            None
-        }
-
-        fn solve(&mut self) -> Option<u32> {
-            return Some(self.yes_count + self.questions.count_ones());
-        }
-
-    }
-
-}
-
-
-pub mod part_2 {
-
-    use super::*;
-
-    pub struct State {
-        yes_questions: Questions,
-        yes_count: u32
-    }
-
-    impl super::CumulativeSolver for State {
-
-        type Input = Row;
-        type Output = u32;
-
-        fn deserialize(&self, line: &std::string::String) -> Option<Row> {
-
-            if line.is_empty() {
-                return Some(Row::GroupTerminator);
-            }
-
-            let mut questions: Questions = 0;
-            for c in line.chars() {
-                if c < 'a' || c > 'z' {
-                    return None
-                }
-                let question_index = (c as u32) - ('a' as u32);
-                questions |= 1 << question_index;
-            }
-
-            return Some(Row::Response(questions))
-
-        }
-
-        fn serialize(&self, output: &u32) -> std::string::String {
-            output.to_string()
-        }
-
-        fn new() -> Self {
-            State {
-                yes_questions: ALL_QUESTIONS,
-                yes_count: 0,
-            }
-        }
-
-        fn accumulate(&mut self, row: Row) -> Option<u32> {
-            match row {
-                Row::GroupTerminator => {
-                    self.yes_count += self.yes_questions.count_ones();
-                    self.yes_questions = ALL_QUESTIONS;
-                },
-                Row::Response(questions) => {
-                    self.yes_questions &= questions;
-                },
-            }
-            None
-        }
-
-        fn solve(&mut self) -> Option<u32> {
-            return Some(self.yes_count + self.yes_questions.count_ones());
-        }
-
-    }
-
-}
+use super::GroupedSolver;
+use super::{DayRegistration, Grouped, GroupedDay, Registration, Registry, SolverKind};
+use crate::solve::SolveError;
+use crate::parse::ParseError;
+
+const TITLE: &str = "Custom Customs";
+
+type Questions = u32;
+
+const ALL_QUESTIONS: Questions = 0x03ffffff;
+
+fn deserialize_response(line: &str) -> Result<Questions, ParseError> {
+    let mut questions: Questions = 0;
+    for (column, c) in line.char_indices() {
+        if !c.is_ascii_lowercase() {
+            return Err(ParseError::new(line, column..(column + c.len_utf8()), "a question from 'a' to 'z'"))
+        }
+        let question_index = (c as u32) - ('a' as u32);
+        questions |= 1 << question_index;
+    }
+    Ok(questions)
+}
+
+// The questions each person in the group answered yes to.
+fn deserialize_group(lines: &[String]) -> Result<Vec<Questions>, ParseError> {
+    lines.iter().enumerate().map(|(line_index, line)| {
+        deserialize_response(line).map_err(|e| e.on_line(line_index))
+    }).collect()
+}
+
+pub mod part_1 {
+
+    use super::*;
+
+    pub struct State {
+        yes_count: u32
+    }
+
+    impl super::GroupedSolver for State {
+
+        type Group = Vec<Questions>;
+        type Output = u32;
+
+        fn deserialize(&self, lines: &[String]) -> Result<Vec<Questions>, ParseError> {
+            deserialize_group(lines)
+        }
+
+        fn serialize(&self, output: &u32) -> std::string::String {
+            output.to_string()
+        }
+
+        fn new() -> Self {
+            State {
+                yes_count: 0,
+            }
+        }
+
+        // Questions anyone in the group answered yes to.
+        fn accumulate(&mut self, group: Vec<Questions>) -> Option<u32> {
+            self.yes_count += group.iter().fold(0, |yes_questions, questions| yes_questions | questions).count_ones();
+            None
+        }
+
+        fn solve(&mut self) -> Result<u32, SolveError> {
+            Ok(self.yes_count)
+        }
+
+    }
+
+}
+
+
+pub mod part_2 {
+
+    use super::*;
+
+    pub struct State {
+        yes_count: u32
+    }
+
+    impl super::GroupedSolver for State {
+
+        type Group = Vec<Questions>;
+        type Output = u32;
+
+        fn deserialize(&self, lines: &[String]) -> Result<Vec<Questions>, ParseError> {
+            deserialize_group(lines)
+        }
+
+        fn serialize(&self, output: &u32) -> std::string::String {
+            output.to_string()
+        }
+
+        fn new() -> Self {
+            State {
+                yes_count: 0,
+            }
+        }
+
+        // Questions everyone in the group answered yes to.
+        fn accumulate(&mut self, group: Vec<Questions>) -> Option<u32> {
+            self.yes_count += group.iter().fold(ALL_QUESTIONS, |yes_questions, questions| yes_questions & questions).count_ones();
+            None
+        }
+
+        fn solve(&mut self) -> Result<u32, SolveError> {
+            Ok(self.yes_count)
+        }
+
+    }
+
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.add(
+        Registration {
+            day_index: 6,
+            part_index: 1,
+            title: TITLE,
+            kind: SolverKind::Grouped,
+            solver: || Box::new(Grouped::<part_1::State>::new()),
+        }
+    );
+    registry.add(
+        Registration {
+            day_index: 6,
+            part_index: 2,
+            title: TITLE,
+            kind: SolverKind::Grouped,
+            solver: || Box::new(Grouped::<part_2::State>::new()),
+        }
+    );
+    registry.add_day(
+        DayRegistration {
+            day_index: 6,
+            title: TITLE,
+            solver: || Box::new(GroupedDay::<part_1::State, part_2::State>::new()),
+        }
+    );
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::days::{solve_day_example, solve_example};
+
+    // The example from the puzzle.
+    const EXAMPLE: &str = "\
+abc
+
+a
+b
+c
+
+ab
+ac
+
+a
+a
+a
+a
+
+b";
+
+    #[test]
+    fn part_1_example() {
+        assert_eq!(solve_example(Grouped::<part_1::State>::new(), EXAMPLE), "11");
+    }
+
+    #[test]
+    fn part_2_example() {
+        assert_eq!(solve_example(Grouped::<part_2::State>::new(), EXAMPLE), "6");
+    }
+
+    #[test]
+    fn both_parts_example() {
+        assert_eq!(solve_day_example(GroupedDay::<part_1::State, part_2::State>::new(), EXAMPLE), ["11", "6"]);
+    }
+
+    // Extra blank lines, before, between and after groups, don't make empty groups.
+    #[test]
+    fn extra_blank_lines() {
+        assert_eq!(solve_example(Grouped::<part_1::State>::new(), "\nabc\n\n\nab\nb\n\n"), "5");
+        assert_eq!(solve_example(Grouped::<part_2::State>::new(), "\nabc\n\n\nab\nb\n\n"), "4");
+    }
+
+    #[test]
+    fn parse_error_gives_group_and_line() {
+        let result = crate::runner::solve_str(&mut Grouped::<part_1::State>::new(), "abc\n\n\nab\na1");
+        match result {
+            Err(crate::runner::RunError::Parse(e)) => {
+                assert_eq!((e.group_index, e.line_index, e.columns), (Some(1), 4, 1..2));
+            },
+            _ => panic!("expected a parse error, got {:?}", result),
+        }
+    }
+
+}
@@ -1,226 +1,331 @@
-use super::CumulativeSolver;
-use std::collections::HashSet;
-
-#[derive(Copy, Clone, Debug)]
-pub enum Instruction {
-    Accumulate(i32),
-    Jump(i32),
-    NoOperation(i32),
-}
-
-#[derive(PartialEq)]
-enum Outcome {
-    InfiniteLoop,
-    InvalidJump,
-    Termination
-}
-
-#[derive(Clone)]
-struct MachineState {
-    accumulator: i32,
-    instruction_index: u32,
-    executed_instructions: HashSet<u32>,
-}
-
-fn step<F: Fn(u32) -> Instruction>(instruction: F, instructions_length: u32, state: &mut MachineState) -> Option<Outcome> {
-    if state.instruction_index == instructions_length {
-        return Some(Outcome::Termination);
-    }
-    if state.executed_instructions.contains(&state.instruction_index) {
-        return Some(Outcome::InfiniteLoop);
-    }
-    state.executed_instructions.insert(state.instruction_index);
-    match instruction(state.instruction_index) {
-        Instruction::Accumulate(x) => {
-            state.accumulator += x;
-            state.instruction_index += 1;
-        },
-        Instruction::Jump(x) => {
-            let destination_invalid =
-                (x < 0 && state.instruction_index < -x as u32) ||
-                (((instructions_length - state.instruction_index) as i32) < x);
-            if destination_invalid {
-                return Some(Outcome::InvalidJump);
-            } else {
-                state.instruction_index = state.instruction_index.wrapping_add(x as u32);
-            }
-        },
-        Instruction::NoOperation(_) => {
-            state.instruction_index += 1;
-        },
-    }
-    None
-}
-
-fn run(instructions: &Vec<Instruction>) -> (Outcome, i32) {
-    let mut state = MachineState {
-        accumulator: 0,
-        instruction_index: 0,
-        executed_instructions: HashSet::<u32>::new(),
-    };
-    loop {
-        match step(|i| {instructions[i as usize]}, instructions.len() as u32, &mut state) {
-            Some(o) => return (o, state.accumulator),
-            _ => continue,
-        }
-    }
-}
-
-fn deserialize_instruction(line: &std::string::String) -> Option<Instruction> {
-    let instruction_parts: Vec<&str> = line.split(" ").collect();
-    if instruction_parts.len() != 2 {
-        return None;
-    }
-    let argument = instruction_parts[1].parse::<i32>().ok()?;
-    let instruction =
-        match instruction_parts[0] {
-            "acc" => Instruction::Accumulate(argument),
-            "jmp" => Instruction::Jump(argument),
-            "nop" => Instruction::NoOperation(argument),
-            _ => return None,
-        };
-    Some(instruction)
-}
-
-
-pub mod part_1 {
-
-    use super::*;
-
-    pub struct State {
-        instructions: Vec<Instruction>,
-    }
-
-    impl super::CumulativeSolver for State {
-
-        type Input = Instruction;
-        type Output = i32;
-
-        fn deserialize(&self, line: &std::string::String) -> Option<Instruction> {
-            deserialize_instruction(line)
-        }
-
-        fn serialize(&self, output: &i32) -> std::string::String {
-            output.to_string()
-        }
-
-        fn new() -> Self {
-            State {
-                instructions: Vec::new(),
-            }
-        }
-
-        fn accumulate(&mut self, instruction: Instruction) -> Option<i32> {
-            self.instructions.push(instruction);
-            None
-        }
-
-        fn solve(&mut self) -> Option<i32> {
-            match run(&self.instructions) {
-                (Outcome::InfiniteLoop, a) => Some(a),
-                _ => None
-            }
-        }
-
-    }
-
-}
-
-pub mod part_2 {
-
-    use super::*;
-
-    pub struct State {
-        instructions: Vec<Instruction>,
-    }
-
-    fn corrupted(inst: &Instruction) -> Instruction {
-        match inst {
-            Instruction::Jump(x) => Instruction::NoOperation(*x),
-            Instruction::NoOperation(x) => Instruction::Jump(*x),
-            _ => *inst,
-        }
-    }
-
-    fn possibly_corrupt(inst: &Instruction) -> bool {
-        match inst {
-            Instruction::Jump(_) => true,
-            Instruction::NoOperation(_) => true,
-            _ => false,
-        }
-    }
-
-    impl super::CumulativeSolver for State {
-
-        type Input = Instruction;
-        type Output = i32;
-
-        fn deserialize(&self, line: &std::string::String) -> Option<Instruction> {
-            deserialize_instruction(line)
-        }
-
-        fn serialize(&self, output: &i32) -> std::string::String {
-            output.to_string()
-        }
-
-        fn new() -> Self {
-            State {
-                instructions: Vec::new(),
-            }
-        }
-
-        fn accumulate(&mut self, instruction: Instruction) -> Option<i32> {
-            self.instructions.push(instruction);
-            None
-        }
-
-        fn solve(&mut self) -> Option<i32> {
-            let mut state = MachineState {
-                accumulator: 0,
-                instruction_index: 0,
-                executed_instructions: HashSet::<u32>::new(),
-            };
-
-            loop {
-                let instruction = &self.instructions[state.instruction_index as usize];
-
-                // Go on an excursion if it's possible to corrupt the instruction.
-                if possibly_corrupt(instruction) {
-                    let corrupted_instruction_index = Some(state.instruction_index);
-                    let current_instruction = |instruction_index| {
-                        let uncorrupted = &self.instructions[instruction_index as usize];
-                        if corrupted_instruction_index == Some(instruction_index) {
-                            corrupted(uncorrupted)
-                        } else {
-                            *uncorrupted
-                        }
-                    };
-
-                    let mut excursion_state = state.clone();
-                    let excursion_outcome =
-                        loop {
-                            let step_outcome = step(current_instruction,
-                                                    self.instructions.len() as u32, &mut excursion_state);
-                            match step_outcome {
-                                Some(o) => break o,
-                                _ => continue,
-                            }
-                        };
-                    if excursion_outcome == Outcome::Termination {
-                        return Some(excursion_state.accumulator);
-                    }
-                }
-
-                // The excursion yielded nothing, so continue as normal.
-                match step(|_| {*instruction},
-                           self.instructions.len() as u32, &mut state) {
-                    Some(_) => break None,
-                    None => continue,
-                }
-            }
-
-        }
-
-    }
-
-}
+use super::CumulativeSolver;
+use super::{Cumulative, CumulativeDay, DayRegistration, Registration, Registry, SolverKind};
+use crate::parse::ParseError;
+use crate::solve::{Cancellation, Progress, SolveError};
+use std::collections::HashSet;
+
+const TITLE: &str = "Handheld Halting";
+
+#[derive(Copy, Clone, Debug)]
+pub enum Instruction {
+    Accumulate(i32),
+    Jump(i32),
+    NoOperation(i32),
+}
+
+#[derive(PartialEq)]
+enum Outcome {
+    InfiniteLoop,
+    InvalidJump,
+    AccumulatorOverflow,
+    Termination
+}
+
+#[derive(Clone)]
+struct MachineState {
+    accumulator: i32,
+    instruction_index: u32,
+    executed_instructions: HashSet<u32>,
+}
+
+fn step<F: Fn(u32) -> Instruction>(instruction: F, instructions_length: u32, state: &mut MachineState) -> Option<Outcome> {
+    if state.instruction_index == instructions_length {
+        return Some(Outcome::Termination);
+    }
+    if state.executed_instructions.contains(&state.instruction_index) {
+        return Some(Outcome::InfiniteLoop);
+    }
+    state.executed_instructions.insert(state.instruction_index);
+    match instruction(state.instruction_index) {
+        Instruction::Accumulate(x) => {
+            match state.accumulator.checked_add(x) {
+                None => return Some(Outcome::AccumulatorOverflow),
+                Some(accumulator) => state.accumulator = accumulator,
+            }
+            state.instruction_index += 1;
+        },
+        Instruction::Jump(x) => {
+            // Jumping to just past the last instruction is how a program terminates.
+            let destination = state.instruction_index as i64 + x as i64;
+            if destination < 0 || destination > instructions_length as i64 {
+                return Some(Outcome::InvalidJump);
+            }
+            state.instruction_index = destination as u32;
+        },
+        Instruction::NoOperation(_) => {
+            state.instruction_index += 1;
+        },
+    }
+    None
+}
+
+fn run(instructions: &[Instruction]) -> (Outcome, i32) {
+    let mut state = MachineState {
+        accumulator: 0,
+        instruction_index: 0,
+        executed_instructions: HashSet::<u32>::new(),
+    };
+    loop {
+        match step(|i| {instructions[i as usize]}, instructions.len() as u32, &mut state) {
+            Some(o) => return (o, state.accumulator),
+            _ => continue,
+        }
+    }
+}
+
+fn deserialize_instruction(line: &str) -> Result<Instruction, ParseError> {
+    let instruction_parts: Vec<&str> = line.split(' ').collect();
+    if instruction_parts.len() != 2 {
+        return Err(ParseError::new(line, 0..line.len(), "an operation and an argument"));
+    }
+    let operation_length = instruction_parts[0].len();
+    let argument =
+        instruction_parts[1].parse::<i32>().map_err(|_| {
+            ParseError::new(line, (operation_length + 1)..line.len(), "a signed argument")
+        })?;
+    let instruction =
+        match instruction_parts[0] {
+            "acc" => Instruction::Accumulate(argument),
+            "jmp" => Instruction::Jump(argument),
+            "nop" => Instruction::NoOperation(argument),
+            _ => return Err(ParseError::new(line, 0..operation_length, "'acc', 'jmp' or 'nop'")),
+        };
+    Ok(instruction)
+}
+
+
+pub mod part_1 {
+
+    use super::*;
+
+    pub struct State {
+        instructions: Vec<Instruction>,
+    }
+
+    impl super::CumulativeSolver for State {
+
+        type Input = Instruction;
+        type Output = i32;
+
+        fn deserialize(&self, line: &str) -> Result<Instruction, ParseError> {
+            deserialize_instruction(line)
+        }
+
+        fn serialize(&self, output: &i32) -> std::string::String {
+            output.to_string()
+        }
+
+        fn new() -> Self {
+            State {
+                instructions: Vec::new(),
+            }
+        }
+
+        fn accumulate(&mut self, instruction: Instruction) -> Option<i32> {
+            self.instructions.push(instruction);
+            None
+        }
+
+        fn solve(&mut self) -> Result<i32, SolveError> {
+            match run(&self.instructions) {
+                (Outcome::InfiniteLoop, a) => Ok(a),
+                (Outcome::Termination, _) =>
+                    Err(SolveError::NoSolution("the program terminates instead of looping".to_string())),
+                (Outcome::InvalidJump, _) =>
+                    Err(SolveError::PreconditionViolated("the program jumps outside of itself".to_string())),
+                (Outcome::AccumulatorOverflow, _) =>
+                    Err(SolveError::LimitExceeded("the accumulator overflows 32 bits".to_string())),
+            }
+        }
+
+    }
+
+}
+
+pub mod part_2 {
+
+    use super::*;
+
+    pub struct State {
+        instructions: Vec<Instruction>,
+        cancellation: Cancellation,
+        progress: Progress,
+    }
+
+    fn corrupted(inst: &Instruction) -> Instruction {
+        match inst {
+            Instruction::Jump(x) => Instruction::NoOperation(*x),
+            Instruction::NoOperation(x) => Instruction::Jump(*x),
+            _ => *inst,
+        }
+    }
+
+    fn possibly_corrupt(inst: &Instruction) -> bool {
+        matches!(inst, Instruction::Jump(_) | Instruction::NoOperation(_))
+    }
+
+    impl super::CumulativeSolver for State {
+
+        type Input = Instruction;
+        type Output = i32;
+
+        fn deserialize(&self, line: &str) -> Result<Instruction, ParseError> {
+            deserialize_instruction(line)
+        }
+
+        fn serialize(&self, output: &i32) -> std::string::String {
+            output.to_string()
+        }
+
+        fn new() -> Self {
+            State {
+                instructions: Vec::new(),
+                cancellation: Cancellation::new(),
+                progress: Progress::new(),
+            }
+        }
+
+        fn accumulate(&mut self, instruction: Instruction) -> Option<i32> {
+            self.instructions.push(instruction);
+            None
+        }
+
+        fn solve(&mut self) -> Result<i32, SolveError> {
+            let mut state = MachineState {
+                accumulator: 0,
+                instruction_index: 0,
+                executed_instructions: HashSet::<u32>::new(),
+            };
+            self.progress.start(None, "instructions executed");
+
+            loop {
+                let instruction =
+                    match self.instructions.get(state.instruction_index as usize) {
+                        None => {
+                            let reason = "the program terminates without flipping any instruction";
+                            break Err(SolveError::PreconditionViolated(reason.to_string()));
+                        },
+                        Some(instruction) => instruction,
+                    };
+
+                // Go on an excursion if it's possible to corrupt the instruction.
+                if possibly_corrupt(instruction) {
+                    self.cancellation.check()?;
+                    let corrupted_instruction_index = Some(state.instruction_index);
+                    let current_instruction = |instruction_index| {
+                        let uncorrupted = &self.instructions[instruction_index as usize];
+                        if corrupted_instruction_index == Some(instruction_index) {
+                            corrupted(uncorrupted)
+                        } else {
+                            *uncorrupted
+                        }
+                    };
+
+                    let mut excursion_state = state.clone();
+                    let excursion_outcome =
+                        loop {
+                            let step_outcome = step(current_instruction,
+                                                    self.instructions.len() as u32, &mut excursion_state);
+                            match step_outcome {
+                                Some(o) => break o,
+                                _ => continue,
+                            }
+                        };
+                    let excursion_length = excursion_state.executed_instructions.len() - state.executed_instructions.len();
+                    self.progress.advance(excursion_length as u64);
+                    if excursion_outcome == Outcome::Termination {
+                        return Ok(excursion_state.accumulator);
+                    }
+                }
+
+                // The excursion yielded nothing, so continue as normal.
+                self.progress.advance(1);
+                match step(|_| {*instruction},
+                           self.instructions.len() as u32, &mut state) {
+                    Some(Outcome::AccumulatorOverflow) => {
+                        break Err(SolveError::LimitExceeded("the accumulator overflows 32 bits".to_string()));
+                    },
+                    Some(_) => {
+                        let reason = "no single flipped instruction makes the program terminate";
+                        break Err(SolveError::NoSolution(reason.to_string()));
+                    },
+                    None => continue,
+                }
+            }
+
+        }
+
+        fn set_cancellation(&mut self, cancellation: Cancellation) {
+            self.cancellation = cancellation;
+        }
+
+        fn set_progress(&mut self, progress: Progress) {
+            self.progress = progress;
+        }
+
+    }
+
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.add(
+        Registration {
+            day_index: 8,
+            part_index: 1,
+            title: TITLE,
+            kind: SolverKind::Cumulative,
+            solver: || Box::new(Cumulative::<part_1::State>::new()),
+        }
+    );
+    registry.add(
+        Registration {
+            day_index: 8,
+            part_index: 2,
+            title: TITLE,
+            kind: SolverKind::Cumulative,
+            solver: || Box::new(Cumulative::<part_2::State>::new()),
+        }
+    );
+    registry.add_day(
+        DayRegistration {
+            day_index: 8,
+            title: TITLE,
+            solver: || Box::new(CumulativeDay::<part_1::State, part_2::State>::new()),
+        }
+    );
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::days::{solve_day_example, solve_example};
+
+    // The example from the puzzle.
+    const EXAMPLE: &str = "\
+nop +0
+acc +1
+jmp +4
+acc +3
+jmp -3
+acc -99
+acc +1
+jmp -4
+acc +6";
+
+    #[test]
+    fn part_1_example() {
+        assert_eq!(solve_example(Cumulative::<part_1::State>::new(), EXAMPLE), "5");
+    }
+
+    #[test]
+    fn part_2_example() {
+        assert_eq!(solve_example(Cumulative::<part_2::State>::new(), EXAMPLE), "8");
+    }
+
+    #[test]
+    fn both_parts_example() {
+        assert_eq!(solve_day_example(CumulativeDay::<part_1::State, part_2::State>::new(), EXAMPLE), ["5", "8"]);
+    }
+
+}
@@ -1,24 +1,641 @@
-pub mod day_1;
-pub mod day_2;
-pub mod day_3;
-pub mod day_6;
-pub mod day_8;
-pub mod day_12;
-pub mod day_13;
-pub mod day_15;
-
-pub trait CumulativeSolver {
-    type Input;
-    type Output;
-    fn deserialize(&self, line: &std::string::String) -> Option<Self::Input>;
-    fn serialize(&self, output: &Self::Output) -> std::string::String;
-    fn new() -> Self;
-    // Returns some output if it's done early.
-    fn accumulate(&mut self, x: Self::Input) -> Option<Self::Output>;
-    fn solve(&mut self) -> Option<Self::Output>;
-}
-
-pub trait FixedLineCountSolver {
-    fn from_input(lines: &[String]) -> Option<Self> where Self: Sized;
-    fn solve(&mut self) -> Option<u32>;
-}
+use std::fmt;
+use std::io;
+use crate::parse::ParseError;
+use crate::runner::RunError;
+use crate::solve::{Cancellation, Progress, SolveError};
+
+pub mod day_1;
+pub mod day_2;
+pub mod day_3;
+pub mod day_6;
+pub mod day_8;
+pub mod day_12;
+pub mod day_13;
+pub mod day_15;
+
+// The event these days are from.
+pub const YEAR: u32 = 2020;
+
+pub trait CumulativeSolver {
+    type Input;
+    type Output;
+    fn deserialize(&self, line: &str) -> Result<Self::Input, ParseError>;
+    fn serialize(&self, output: &Self::Output) -> std::string::String;
+    fn new() -> Self;
+    // Returns some output if it's done early.
+    fn accumulate(&mut self, x: Self::Input) -> Option<Self::Output>;
+    fn solve(&mut self) -> Result<Self::Output, SolveError>;
+    // Solvers with long loops keep these, poll the first and update the second while solving.
+    fn set_cancellation(&mut self, _cancellation: Cancellation) {}
+    fn set_progress(&mut self, _progress: Progress) {}
+}
+
+pub trait FixedLineCountSolver {
+    // Any answer that can be displayed, like u64, i64, u128 or String.
+    type Output: fmt::Display;
+    fn from_input(lines: &[String]) -> Result<Self, ParseError> where Self: Sized;
+    fn solve(&mut self) -> Result<Self::Output, SolveError>;
+    // Solvers with long loops keep these, poll the first and update the second while solving.
+    fn set_cancellation(&mut self, _cancellation: Cancellation) {}
+    fn set_progress(&mut self, _progress: Progress) {}
+}
+
+// Like a cumulative solver, but consumes its input one group of lines at a time, where groups are separated by blank
+// lines. Groups get parsed whole, so a record can span any number of lines.
+pub trait GroupedSolver {
+    type Group;
+    type Output;
+    // Errors give their line counted from the first line of the group.
+    fn deserialize(&self, lines: &[String]) -> Result<Self::Group, ParseError>;
+    fn serialize(&self, output: &Self::Output) -> std::string::String;
+    fn new() -> Self;
+    // Returns some output if it's done early.
+    fn accumulate(&mut self, group: Self::Group) -> Option<Self::Output>;
+    fn solve(&mut self) -> Result<Self::Output, SolveError>;
+    // Solvers with long loops keep these, poll the first and update the second while solving.
+    fn set_cancellation(&mut self, _cancellation: Cancellation) {}
+    fn set_progress(&mut self, _progress: Progress) {}
+}
+
+// What the runner drives: first all the input is read, then it's solved.
+pub trait Solver {
+    // Returns how many lines were consumed, since a solver may be done before the end.
+    fn read(&mut self, lines: &mut dyn Iterator<Item = io::Result<String>>) -> Result<usize, RunError>;
+    fn solve(&mut self) -> Result<String, RunError>;
+    // Should be given before reading, since a fixed line count solver only exists once the input is read.
+    fn set_cancellation(&mut self, cancellation: Cancellation);
+    fn set_progress(&mut self, progress: Progress);
+}
+
+// Adapts a solver that consumes its input one line at a time.
+pub struct Cumulative<S: CumulativeSolver> {
+    solver: S,
+    output: Option<S::Output>,
+}
+
+impl<S: CumulativeSolver> Cumulative<S> {
+    pub fn new() -> Self {
+        Cumulative {
+            solver: S::new(),
+            output: None,
+        }
+    }
+}
+
+impl<S: CumulativeSolver> Default for Cumulative<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: CumulativeSolver> Solver for Cumulative<S> {
+    fn read(&mut self, lines: &mut dyn Iterator<Item = io::Result<String>>) -> Result<usize, RunError> {
+        let mut line_count = 0;
+        for (line_index, line_result) in lines.enumerate() {
+            let line = line_result.map_err(RunError::Read)?;
+            let input = self.solver.deserialize(&line).map_err(|e| RunError::Parse(e.on_line(line_index)))?;
+            line_count += 1;
+            self.output = self.solver.accumulate(input);
+            if self.output.is_some() {
+                break;
+            }
+        }
+        Ok(line_count)
+    }
+
+    fn solve(&mut self) -> Result<String, RunError> {
+        let output =
+            match self.output.take() {
+                Some(output) => output,
+                None => self.solver.solve().map_err(RunError::Solve)?,
+            };
+        Ok(self.solver.serialize(&output))
+    }
+
+    fn set_cancellation(&mut self, cancellation: Cancellation) {
+        self.solver.set_cancellation(cancellation);
+    }
+
+    fn set_progress(&mut self, progress: Progress) {
+        self.solver.set_progress(progress);
+    }
+}
+
+// Adapts a solver that needs all of its input at once.
+pub struct FixedLineCount<S: FixedLineCountSolver> {
+    solver: Option<S>,
+    cancellation: Cancellation,
+    progress: Progress,
+}
+
+impl<S: FixedLineCountSolver> FixedLineCount<S> {
+    pub fn new() -> Self {
+        FixedLineCount {
+            solver: None,
+            cancellation: Cancellation::new(),
+            progress: Progress::new(),
+        }
+    }
+}
+
+impl<S: FixedLineCountSolver> Default for FixedLineCount<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: FixedLineCountSolver> Solver for FixedLineCount<S> {
+    fn read(&mut self, lines: &mut dyn Iterator<Item = io::Result<String>>) -> Result<usize, RunError> {
+        let lines = lines.collect::<io::Result<Vec<String>>>().map_err(RunError::Read)?;
+        let mut solver = S::from_input(&lines).map_err(RunError::Parse)?;
+        solver.set_cancellation(self.cancellation.clone());
+        solver.set_progress(self.progress.clone());
+        self.solver = Some(solver);
+        Ok(lines.len())
+    }
+
+    fn solve(&mut self) -> Result<String, RunError> {
+        let solver =
+            self.solver.as_mut().ok_or_else(|| {
+                RunError::Solve(SolveError::PreconditionViolated("the input has not been read".to_string()))
+            })?;
+        let result = solver.solve().map_err(RunError::Solve)?;
+        Ok(result.to_string())
+    }
+
+    fn set_cancellation(&mut self, cancellation: Cancellation) {
+        if let Some(solver) = &mut self.solver {
+            solver.set_cancellation(cancellation.clone());
+        }
+        self.cancellation = cancellation;
+    }
+
+    fn set_progress(&mut self, progress: Progress) {
+        if let Some(solver) = &mut self.solver {
+            solver.set_progress(progress.clone());
+        }
+        self.progress = progress;
+    }
+}
+
+// Hands each group of lines to the given function, until it's done or the input ends, and returns how many lines were
+// consumed. Any number of blank lines separate groups, and the last group needs none after it.
+// Parse errors are moved from the line in their group to the line in the input, and given the group's index.
+fn read_groups(lines: &mut dyn Iterator<Item = io::Result<String>>,
+               mut accumulate: impl FnMut(&[String]) -> Result<bool, ParseError>) -> Result<usize, RunError> {
+    let mut line_count = 0;
+    let mut group_index = 0;
+    let mut first_line_index = 0;
+    let mut group_lines: Vec<String> = Vec::new();
+    // The end of the input ends the last group, just like a blank line.
+    for line_result in lines.map(Some).chain(std::iter::once(None)) {
+        let line = line_result.transpose().map_err(RunError::Read)?;
+        if let Some(line) = line {
+            line_count += 1;
+            if !line.is_empty() {
+                if group_lines.is_empty() {
+                    first_line_index = line_count - 1;
+                }
+                group_lines.push(line);
+                continue;
+            }
+        }
+        if group_lines.is_empty() {
+            continue;
+        }
+        let done =
+            accumulate(&group_lines).map_err(|e| {
+                let line_index = first_line_index + e.line_index;
+                RunError::Parse(e.on_line(line_index).in_group(group_index))
+            })?;
+        group_lines.clear();
+        group_index += 1;
+        if done {
+            break;
+        }
+    }
+    Ok(line_count)
+}
+
+// Adapts a solver that consumes its input one group of lines at a time.
+pub struct Grouped<S: GroupedSolver> {
+    solver: S,
+    output: Option<S::Output>,
+}
+
+impl<S: GroupedSolver> Grouped<S> {
+    pub fn new() -> Self {
+        Grouped {
+            solver: S::new(),
+            output: None,
+        }
+    }
+}
+
+impl<S: GroupedSolver> Default for Grouped<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: GroupedSolver> Solver for Grouped<S> {
+    fn read(&mut self, lines: &mut dyn Iterator<Item = io::Result<String>>) -> Result<usize, RunError> {
+        read_groups(lines, |group_lines| {
+            let group = self.solver.deserialize(group_lines)?;
+            self.output = self.solver.accumulate(group);
+            Ok(self.output.is_some())
+        })
+    }
+
+    fn solve(&mut self) -> Result<String, RunError> {
+        let output =
+            match self.output.take() {
+                Some(output) => output,
+                None => self.solver.solve().map_err(RunError::Solve)?,
+            };
+        Ok(self.solver.serialize(&output))
+    }
+
+    fn set_cancellation(&mut self, cancellation: Cancellation) {
+        self.solver.set_cancellation(cancellation);
+    }
+
+    fn set_progress(&mut self, progress: Progress) {
+        self.solver.set_progress(progress);
+    }
+}
+
+// Answers both parts of a day from a single read of the input.
+pub trait DaySolver {
+    // Returns how many lines were consumed.
+    fn read(&mut self, lines: &mut dyn Iterator<Item = io::Result<String>>) -> Result<usize, RunError>;
+    // Parts are numbered from 1, and answered one at a time so each can be timed.
+    fn solve(&mut self, part_index: u32) -> Result<String, RunError>;
+    // Both parts poll the same cancellation and report to the same progress, one after the other.
+    // Should be given before reading.
+    fn set_cancellation(&mut self, cancellation: Cancellation);
+    fn set_progress(&mut self, progress: Progress);
+}
+
+pub const DAY_PART_COUNT: u32 = 2;
+
+fn no_such_part(part_index: u32) -> SolveError {
+    SolveError::PreconditionViolated(format!("a day has no part {}", part_index))
+}
+
+// Parses each line once with the first part's parser, and feeds it to both parts.
+pub struct CumulativeDay<P1: CumulativeSolver, P2: CumulativeSolver<Input = P1::Input>> {
+    part_1: Cumulative<P1>,
+    part_2: Cumulative<P2>,
+}
+
+impl<P1, P2> CumulativeDay<P1, P2>
+where P1: CumulativeSolver, P2: CumulativeSolver<Input = P1::Input> {
+    pub fn new() -> Self {
+        CumulativeDay {
+            part_1: Cumulative::new(),
+            part_2: Cumulative::new(),
+        }
+    }
+}
+
+impl<P1, P2> Default for CumulativeDay<P1, P2>
+where P1: CumulativeSolver, P2: CumulativeSolver<Input = P1::Input> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P1, P2> DaySolver for CumulativeDay<P1, P2>
+where P1: CumulativeSolver, P2: CumulativeSolver<Input = P1::Input>, P1::Input: Clone {
+    fn read(&mut self, lines: &mut dyn Iterator<Item = io::Result<String>>) -> Result<usize, RunError> {
+        let mut line_count = 0;
+        for (line_index, line_result) in lines.enumerate() {
+            let line = line_result.map_err(RunError::Read)?;
+            let input = self.part_1.solver.deserialize(&line).map_err(|e| RunError::Parse(e.on_line(line_index)))?;
+            line_count += 1;
+            if self.part_1.output.is_none() {
+                self.part_1.output = self.part_1.solver.accumulate(input.clone());
+            }
+            if self.part_2.output.is_none() {
+                self.part_2.output = self.part_2.solver.accumulate(input);
+            }
+            if self.part_1.output.is_some() && self.part_2.output.is_some() {
+                break;
+            }
+        }
+        Ok(line_count)
+    }
+
+    fn solve(&mut self, part_index: u32) -> Result<String, RunError> {
+        match part_index {
+            1 => self.part_1.solve(),
+            2 => self.part_2.solve(),
+            _ => Err(RunError::Solve(no_such_part(part_index))),
+        }
+    }
+
+    fn set_cancellation(&mut self, cancellation: Cancellation) {
+        self.part_1.set_cancellation(cancellation.clone());
+        self.part_2.set_cancellation(cancellation);
+    }
+
+    fn set_progress(&mut self, progress: Progress) {
+        self.part_1.set_progress(progress.clone());
+        self.part_2.set_progress(progress);
+    }
+}
+
+// Parses all of the input once for the first part, and makes the second part from the first.
+pub struct FixedLineCountDay<P1: FixedLineCountSolver, P2: FixedLineCountSolver> {
+    parts: Option<(P1, P2)>,
+    cancellation: Cancellation,
+    progress: Progress,
+}
+
+impl<P1: FixedLineCountSolver, P2: FixedLineCountSolver> FixedLineCountDay<P1, P2> {
+    pub fn new() -> Self {
+        FixedLineCountDay {
+            parts: None,
+            cancellation: Cancellation::new(),
+            progress: Progress::new(),
+        }
+    }
+}
+
+impl<P1: FixedLineCountSolver, P2: FixedLineCountSolver> Default for FixedLineCountDay<P1, P2> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P1, P2> DaySolver for FixedLineCountDay<P1, P2>
+where P1: FixedLineCountSolver, P2: FixedLineCountSolver + for<'a> From<&'a P1> {
+    fn read(&mut self, lines: &mut dyn Iterator<Item = io::Result<String>>) -> Result<usize, RunError> {
+        let lines = lines.collect::<io::Result<Vec<String>>>().map_err(RunError::Read)?;
+        let mut part_1 = P1::from_input(&lines).map_err(RunError::Parse)?;
+        let mut part_2 = P2::from(&part_1);
+        part_1.set_cancellation(self.cancellation.clone());
+        part_2.set_cancellation(self.cancellation.clone());
+        part_1.set_progress(self.progress.clone());
+        part_2.set_progress(self.progress.clone());
+        self.parts = Some((part_1, part_2));
+        Ok(lines.len())
+    }
+
+    fn solve(&mut self, part_index: u32) -> Result<String, RunError> {
+        let (part_1, part_2) =
+            self.parts.as_mut().ok_or_else(|| {
+                RunError::Solve(SolveError::PreconditionViolated("the input has not been read".to_string()))
+            })?;
+        match part_index {
+            1 => part_1.solve().map(|output| output.to_string()).map_err(RunError::Solve),
+            2 => part_2.solve().map(|output| output.to_string()).map_err(RunError::Solve),
+            _ => Err(RunError::Solve(no_such_part(part_index))),
+        }
+    }
+
+    fn set_cancellation(&mut self, cancellation: Cancellation) {
+        self.cancellation = cancellation;
+    }
+
+    fn set_progress(&mut self, progress: Progress) {
+        self.progress = progress;
+    }
+}
+
+// Parses each group once with the first part's parser, and feeds it to both parts.
+pub struct GroupedDay<P1: GroupedSolver, P2: GroupedSolver<Group = P1::Group>> {
+    part_1: Grouped<P1>,
+    part_2: Grouped<P2>,
+}
+
+impl<P1, P2> GroupedDay<P1, P2>
+where P1: GroupedSolver, P2: GroupedSolver<Group = P1::Group> {
+    pub fn new() -> Self {
+        GroupedDay {
+            part_1: Grouped::new(),
+            part_2: Grouped::new(),
+        }
+    }
+}
+
+impl<P1, P2> Default for GroupedDay<P1, P2>
+where P1: GroupedSolver, P2: GroupedSolver<Group = P1::Group> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P1, P2> DaySolver for GroupedDay<P1, P2>
+where P1: GroupedSolver, P2: GroupedSolver<Group = P1::Group>, P1::Group: Clone {
+    fn read(&mut self, lines: &mut dyn Iterator<Item = io::Result<String>>) -> Result<usize, RunError> {
+        let part_1 = &mut self.part_1;
+        let part_2 = &mut self.part_2;
+        read_groups(lines, |group_lines| {
+            let group = part_1.solver.deserialize(group_lines)?;
+            if part_1.output.is_none() {
+                part_1.output = part_1.solver.accumulate(group.clone());
+            }
+            if part_2.output.is_none() {
+                part_2.output = part_2.solver.accumulate(group);
+            }
+            Ok(part_1.output.is_some() && part_2.output.is_some())
+        })
+    }
+
+    fn solve(&mut self, part_index: u32) -> Result<String, RunError> {
+        match part_index {
+            1 => self.part_1.solve(),
+            2 => self.part_2.solve(),
+            _ => Err(RunError::Solve(no_such_part(part_index))),
+        }
+    }
+
+    fn set_cancellation(&mut self, cancellation: Cancellation) {
+        self.part_1.set_cancellation(cancellation.clone());
+        self.part_2.set_cancellation(cancellation);
+    }
+
+    fn set_progress(&mut self, progress: Progress) {
+        self.part_1.set_progress(progress.clone());
+        self.part_2.set_progress(progress);
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SolverKind {
+    Cumulative,
+    FixedLineCount,
+    Grouped,
+}
+
+impl fmt::Display for SolverKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolverKind::Cumulative => write!(f, "cumulative"),
+            SolverKind::FixedLineCount => write!(f, "fixed line count"),
+            SolverKind::Grouped => write!(f, "grouped"),
+        }
+    }
+}
+
+// A solver for one part of one day, along with how to make it.
+#[derive(Copy, Clone)]
+pub struct Registration {
+    pub day_index: u32,
+    pub part_index: u32,
+    pub title: &'static str,
+    pub kind: SolverKind,
+    pub solver: fn() -> Box<dyn Solver>,
+}
+
+// A solver for both parts of one day.
+#[derive(Copy, Clone)]
+pub struct DayRegistration {
+    pub day_index: u32,
+    pub title: &'static str,
+    pub solver: fn() -> Box<dyn DaySolver>,
+}
+
+// The solvers of one event, so each year has a registry of its own.
+pub struct Registry {
+    year: u32,
+    registrations: Vec<Registration>,
+    day_registrations: Vec<DayRegistration>,
+}
+
+impl Registry {
+
+    // Returns a registry holding the solvers of every day of this event.
+    pub fn new() -> Self {
+        let mut registry = Registry::empty(YEAR);
+        day_1::register(&mut registry);
+        day_2::register(&mut registry);
+        day_3::register(&mut registry);
+        day_6::register(&mut registry);
+        day_8::register(&mut registry);
+        day_12::register(&mut registry);
+        day_13::register(&mut registry);
+        day_15::register(&mut registry);
+        registry
+    }
+
+    // Returns a registry without any solvers yet, for the days of another event to register themselves in.
+    pub fn empty(year: u32) -> Self {
+        Registry { year, registrations: Vec::new(), day_registrations: Vec::new() }
+    }
+
+    pub fn year(&self) -> u32 {
+        self.year
+    }
+
+    pub fn add(&mut self, registration: Registration) {
+        self.registrations.push(registration);
+    }
+
+    pub fn find(&self, day_index: u32, part_index: u32) -> Option<&Registration> {
+        self.registrations.iter().find(|r| r.day_index == day_index && r.part_index == part_index)
+    }
+
+    pub fn registrations(&self) -> &[Registration] {
+        &self.registrations
+    }
+
+    pub fn add_day(&mut self, day_registration: DayRegistration) {
+        self.day_registrations.push(day_registration);
+    }
+
+    pub fn find_day(&self, day_index: u32) -> Option<&DayRegistration> {
+        self.day_registrations.iter().find(|r| r.day_index == day_index)
+    }
+
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Solves an example with a fresh solver, for the tests of each day.
+#[cfg(test)]
+fn solve_example(mut solver: impl Solver, input: &str) -> String {
+    crate::runner::solve_str(&mut solver, input).unwrap()
+}
+
+// Solves both parts of an example from a single read.
+#[cfg(test)]
+fn solve_day_example(mut solver: impl DaySolver, input: &str) -> Vec<String> {
+    let answers = crate::runner::solve_day(&mut solver, &mut input.lines().map(|line| Ok(line.to_string()))).unwrap();
+    answers.into_iter().map(Result::unwrap).collect()
+}
+
+// Solvers that misbehave in the ways a runner has to cope with. They count the lines they're given, and ignore them.
+#[cfg(test)]
+pub(crate) mod stubs {
+
+    use super::*;
+    use std::time::Duration;
+
+    struct Stub {
+        solve: fn() -> Result<String, RunError>,
+    }
+
+    impl Solver for Stub {
+        fn read(&mut self, lines: &mut dyn Iterator<Item = io::Result<String>>) -> Result<usize, RunError> {
+            let mut line_count = 0;
+            for line_result in lines {
+                line_result.map_err(RunError::Read)?;
+                line_count += 1;
+            }
+            Ok(line_count)
+        }
+
+        fn solve(&mut self) -> Result<String, RunError> {
+            (self.solve)()
+        }
+
+        fn set_cancellation(&mut self, _cancellation: Cancellation) {}
+
+        fn set_progress(&mut self, _progress: Progress) {}
+    }
+
+    pub fn panicking() -> Box<dyn Solver> {
+        Box::new(Stub { solve: || panic!("the stub panicked") })
+    }
+
+    // Never polls for cancellation, so it can only be given up on.
+    pub fn never_finishing() -> Box<dyn Solver> {
+        Box::new(
+            Stub {
+                solve: || loop {
+                    std::thread::sleep(Duration::from_millis(10));
+                },
+            }
+        )
+    }
+
+    // Takes this long, and answers with how long it took.
+    pub fn sleeping<const MILLISECONDS: u64>() -> Box<dyn Solver> {
+        Box::new(
+            Stub {
+                solve: || {
+                    std::thread::sleep(Duration::from_millis(MILLISECONDS));
+                    Ok(MILLISECONDS.to_string())
+                },
+            }
+        )
+    }
+
+    pub fn registration(day_index: u32, part_index: u32, solver: fn() -> Box<dyn Solver>) -> Registration {
+        Registration {
+            day_index,
+            part_index,
+            title: "Stub",
+            kind: SolverKind::Cumulative,
+            solver,
+        }
+    }
+
+}
@@ -0,0 +1,20 @@
+use crate::days::{self, Registry};
+
+// An event's solutions, which are registered in a registry of their own.
+pub struct Event {
+    pub year: u32,
+    pub registry: fn() -> Registry,
+    // Where the modules of its days are, relative to the repository root.
+    pub days_directory: &'static str,
+}
+
+pub const EVENTS: [Event; 1] = [
+    Event { year: days::YEAR, registry: Registry::new, days_directory: "src/days" },
+];
+
+// The year of the event to use when none is given.
+pub const DEFAULT_YEAR: u32 = days::YEAR;
+
+pub fn find(year: u32) -> Option<&'static Event> {
+    EVENTS.iter().find(|event| event.year == year)
+}
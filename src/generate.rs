@@ -0,0 +1,242 @@
+// Random inputs for each day, always valid, and the same for the same seed and size.
+
+// Every generator makes inputs for the puzzles of this event.
+pub const YEAR: u32 = crate::days::YEAR;
+
+// A xorshift generator, which is plenty for making up puzzle inputs.
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+
+    // A seed of 0 would only ever give 0, so it's taken as 1.
+    pub fn new(seed: u64) -> Self {
+        Random {
+            state: seed.max(1),
+        }
+    }
+
+    pub fn number(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.number() % bound
+    }
+
+    // An index into something of this length, which mustn't be empty.
+    pub fn index(&mut self, length: usize) -> usize {
+        self.below(length as u64) as usize
+    }
+
+    // Between low and high, both included.
+    pub fn between(&mut self, low: u64, high: u64) -> u64 {
+        low + self.below(high - low + 1)
+    }
+
+    pub fn chance(&mut self, numerator: u64, denominator: u64) -> bool {
+        self.below(denominator) < numerator
+    }
+
+    pub fn letter(&mut self) -> char {
+        (b'a' + self.below(26) as u8) as char
+    }
+
+}
+
+// Makes inputs for one day, where the size is counted in whatever the day's input is made of.
+pub struct Generator {
+    pub day_index: u32,
+    // Like entries, rows, groups or instructions.
+    pub unit: &'static str,
+    // About the size of a real input.
+    pub default_size: usize,
+    pub generate: fn(&mut Random, usize) -> String,
+}
+
+pub const GENERATORS: [Generator; 8] = [
+    Generator { day_index: 1, unit: "entries", default_size: 200, generate: day_1 },
+    Generator { day_index: 2, unit: "entries", default_size: 1000, generate: day_2 },
+    Generator { day_index: 3, unit: "rows", default_size: 323, generate: day_3 },
+    Generator { day_index: 6, unit: "groups", default_size: 490, generate: day_6 },
+    Generator { day_index: 8, unit: "instructions", default_size: 650, generate: day_8_looping },
+    Generator { day_index: 12, unit: "instructions", default_size: 780, generate: day_12 },
+    Generator { day_index: 13, unit: "bus slots", default_size: 90, generate: day_13 },
+    Generator { day_index: 15, unit: "starting numbers", default_size: 7, generate: day_15 },
+];
+
+pub fn find(day_index: u32) -> Option<&'static Generator> {
+    GENERATORS.iter().find(|g| g.day_index == day_index)
+}
+
+// Makes an input of the given size for a day, or nothing if there's no generator for it.
+pub fn generate(day_index: u32, size: usize, seed: u64) -> Option<String> {
+    find(day_index).map(|generator| (generator.generate)(&mut Random::new(seed), size))
+}
+
+// Exactly one pair and one triple of entries sum to 2020, so both parts have a single answer.
+// Every other entry is above 1010, so two of them always sum to more than 2020. The rest is picking
+// the three small entries so that no other combination of them, with a large entry, sums to 2020.
+pub fn day_1(random: &mut Random, size: usize) -> String {
+    let (pair, triple) =
+        loop {
+            let small = [random.between(1, 1009), random.between(1, 500), random.between(1, 500)];
+            let mut sums = Vec::new();
+            for (i, x) in small.iter().enumerate() {
+                sums.push(*x);
+                for y in &small[i..] {
+                    sums.push(x + y);
+                }
+            }
+            let mut sorted_sums = sums.clone();
+            sorted_sums.sort_unstable();
+            sorted_sums.dedup();
+            let small_triple_sums_to_2020 =
+                small.iter().any(|x| small.iter().any(|y| small.iter().any(|z| x + y + z == 2020)));
+            if sorted_sums.len() == sums.len() && !small_triple_sums_to_2020 {
+                break ([small[0], 2020 - small[0]], [small[1], small[2], 2020 - small[1] - small[2]]);
+            }
+        };
+    // A large entry could only sum to 2020 with small ones if it's 2020 minus one or two of them.
+    let excluded = |entry: u64| {
+        pair.iter().chain(triple.iter()).any(|x| {
+            entry + x == 2020 || pair.iter().chain(triple.iter()).any(|y| entry + x + y == 2020)
+        })
+    };
+    let mut entries: Vec<u64> = pair.iter().chain(triple.iter()).cloned().collect();
+    while entries.len() < size.max(5) {
+        let entry = random.between(1011, 2020);
+        if !excluded(entry) {
+            entries.push(entry);
+        }
+    }
+    shuffle(random, &mut entries);
+    entries.iter().map(|entry| format!("{}\n", entry)).collect()
+}
+
+// Passwords are at least as long as the maximum, so both positions of part 2 exist.
+pub fn day_2(random: &mut Random, size: usize) -> String {
+    let mut input = String::new();
+    for _ in 0..size {
+        let min = random.between(1, 5);
+        let max = min + random.below(10);
+        let character = random.letter();
+        let length = max + random.below(5);
+        let password: String =
+            (0..length).map(|_| if random.chance(1, 3) { character } else { random.letter() }).collect();
+        input += &format!("{}-{} {}: {}\n", min, max, character, password);
+    }
+    input
+}
+
+// A map as wide as the real ones, with about a quarter of the squares being trees.
+pub fn day_3(random: &mut Random, size: usize) -> String {
+    let mut input = String::new();
+    for _ in 0..size.max(1) {
+        let row: String = (0..31).map(|_| if random.chance(1, 4) { '#' } else { '.' }).collect();
+        input += &row;
+        input += "\n";
+    }
+    input
+}
+
+// Groups of one to five people, each answering yes to at least one question.
+pub fn day_6(random: &mut Random, size: usize) -> String {
+    let mut groups = Vec::new();
+    for _ in 0..size {
+        let person_count = random.between(1, 5);
+        let mut group = String::new();
+        for _ in 0..person_count {
+            let mut questions: Vec<char> = ('a'..='z').filter(|_| random.chance(1, 4)).collect();
+            if questions.is_empty() {
+                questions.push(random.letter());
+            }
+            shuffle(random, &mut questions);
+            group += &questions.into_iter().collect::<String>();
+            group += "\n";
+        }
+        groups.push(group);
+    }
+    groups.join("\n")
+}
+
+// Runs straight through, jumping over some instructions, until it reaches a final jump back to the start.
+// So the program loops, and flipping that final jump is the only way to make it terminate.
+pub fn day_8_looping(random: &mut Random, size: usize) -> String {
+    let instruction_count = size.max(2);
+    let mut input = day_8_forward(random, instruction_count - 1, instruction_count - 1);
+    input += &format!("jmp -{}\n", instruction_count - 1);
+    input
+}
+
+// Runs straight through, jumping over some instructions, until it ends. So the program terminates.
+pub fn day_8_terminating(random: &mut Random, size: usize) -> String {
+    day_8_forward(random, size.max(1), size.max(1))
+}
+
+// Jumps only go forward, to at most the given index, and so do nops once flipped. A nop can go back though,
+// since flipping one of those just makes the program loop sooner, or jump outside of itself.
+fn day_8_forward(random: &mut Random, instruction_count: usize, last_index: usize) -> String {
+    let mut input = String::new();
+    for index in 0..instruction_count {
+        let furthest_offset = ((last_index - index) as u64).min(10);
+        let instruction =
+            match random.below(10) {
+                0 if furthest_offset > 0 => format!("jmp +{}", random.between(1, furthest_offset)),
+                1 => format!("nop {:+}", random.below(11 + furthest_offset) as i64 - 10),
+                _ => format!("acc {:+}", random.below(201) as i64 - 100),
+            };
+        input += &instruction;
+        input += "\n";
+    }
+    input
+}
+
+pub fn day_12(random: &mut Random, size: usize) -> String {
+    let mut input = String::new();
+    for _ in 0..size {
+        let instruction =
+            match random.below(7) {
+                0 => format!("L{}", 90 * random.between(1, 3)),
+                1 => format!("R{}", 90 * random.between(1, 3)),
+                action => format!("{}{}", ['N', 'S', 'E', 'W', 'F'][action as usize - 2], random.between(1, 100)),
+            };
+        input += &instruction;
+        input += "\n";
+    }
+    input
+}
+
+// About one slot in eight has a bus in service, and there's always at least one.
+pub fn day_13(random: &mut Random, size: usize) -> String {
+    let slot_count = size.max(1);
+    let bus_index = random.index(slot_count);
+    let slots: Vec<String> =
+        (0..slot_count).map(|index| {
+            if index == bus_index || random.chance(1, 8) {
+                random.between(2, 1000).to_string()
+            } else {
+                "x".to_string()
+            }
+        }).collect();
+    format!("{}\n{}\n", random.between(1, 1_000_000), slots.join(","))
+}
+
+// Starting numbers are all different, like in the puzzle.
+pub fn day_15(random: &mut Random, size: usize) -> String {
+    let mut numbers: Vec<u64> = (0..(2 * size.max(1) as u64)).collect();
+    shuffle(random, &mut numbers);
+    numbers.truncate(size.max(1));
+    let numbers: Vec<String> = numbers.iter().map(|n| n.to_string()).collect();
+    format!("{}\n", numbers.join(","))
+}
+
+fn shuffle<T>(random: &mut Random, items: &mut [T]) {
+    for i in (1..items.len()).rev() {
+        items.swap(i, random.index(i + 1));
+    }
+}
@@ -0,0 +1,273 @@
+use crate::parse::ParseError;
+
+// A column and a row, which can be outside of the grid, or negative, until they're resolved with an edge.
+pub type Position = (i64, i64);
+
+// What happens to positions outside of the grid.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Edge {
+    // There's nothing there.
+    Bounded,
+    // The grid repeats in every direction.
+    Wrap,
+    // It's the nearest cell on the edge.
+    Clamp,
+}
+
+// Which cells around a cell are its neighbors.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Neighborhood {
+    // Up, right, down and left.
+    Orthogonal,
+    // Also the four diagonals.
+    All,
+    // Hexagons in axial coordinates, where the rows are slanted so that the cells up and right, and down and left,
+    // are neighbors, and the other two diagonals aren't.
+    Hex,
+}
+
+const ORTHOGONAL_OFFSETS: [Position; 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+const ALL_OFFSETS: [Position; 8] = [(0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1)];
+const HEX_OFFSETS: [Position; 6] = [(0, -1), (1, -1), (1, 0), (0, 1), (-1, 1), (-1, 0)];
+
+impl Neighborhood {
+    fn offsets(self) -> &'static [Position] {
+        match self {
+            Neighborhood::Orthogonal => &ORTHOGONAL_OFFSETS,
+            Neighborhood::All => &ALL_OFFSETS,
+            Neighborhood::Hex => &HEX_OFFSETS,
+        }
+    }
+}
+
+// Cells in rows of the same width, stored row after row.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+
+    pub fn new(width: usize, height: usize, cell: T) -> Self where T: Clone {
+        Grid {
+            width,
+            height,
+            cells: vec![cell; width * height],
+        }
+    }
+
+    // Reads a map with one character per cell, where every row is as wide as the first.
+    // The cell function turns a character into a cell, or nothing if it isn't one, which is described by expected.
+    pub fn parse(lines: &[String], expected: &str, cell: impl Fn(char) -> Option<T>) -> Result<Self, ParseError> {
+        let mut cells = Vec::new();
+        let mut width = None;
+        for (line_index, line) in lines.iter().enumerate() {
+            let row_expected =
+                match width {
+                    None => "a row of at least 1 cell".to_string(),
+                    Some(width) => format!("a row of {} cells, like the first", width),
+                };
+            let mut row_width = 0;
+            for (column, c) in line.char_indices() {
+                if Some(row_width) == width {
+                    let error =
+                        ParseError::new(line, column..line.len(), &row_expected)
+                            .with_found(&format!("{} cells", line.chars().count()));
+                    return Err(error.on_line(line_index));
+                }
+                let error = || ParseError::new(line, column..(column + c.len_utf8()), expected).on_line(line_index);
+                cells.push(cell(c).ok_or_else(error)?);
+                row_width += 1;
+            }
+            if row_width == 0 || width.is_some_and(|width| width != row_width) {
+                let error =
+                    ParseError::new(line, line.len()..line.len(), &row_expected)
+                        .with_found(&format!("{} cells", row_width));
+                return Err(error.on_line(line_index));
+            }
+            width = Some(row_width);
+        }
+        let width = width.ok_or_else(|| ParseError::new("", 0..0, "a map").with_found("end of input"))?;
+        Ok(
+            Grid {
+                width,
+                height: lines.len(),
+                cells,
+            }
+        )
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // Where a position ends up with the given edge, or nothing if that's outside of the grid.
+    pub fn resolve(&self, (x, y): Position, edge: Edge) -> Option<Position> {
+        if self.cells.is_empty() {
+            return None;
+        }
+        let (width, height) = (self.width as i64, self.height as i64);
+        let (x, y) =
+            match edge {
+                Edge::Bounded => (x, y),
+                Edge::Wrap => (x.rem_euclid(width), y.rem_euclid(height)),
+                Edge::Clamp => (x.clamp(0, width - 1), y.clamp(0, height - 1)),
+            };
+        if (0..width).contains(&x) && (0..height).contains(&y) {
+            Some((x, y))
+        } else {
+            None
+        }
+    }
+
+    fn index(&self, position: Position, edge: Edge) -> Option<usize> {
+        self.resolve(position, edge).map(|(x, y)| y as usize * self.width + x as usize)
+    }
+
+    pub fn get(&self, position: Position, edge: Edge) -> Option<&T> {
+        self.index(position, edge).map(|index| &self.cells[index])
+    }
+
+    pub fn get_mut(&mut self, position: Position, edge: Edge) -> Option<&mut T> {
+        self.index(position, edge).map(move |index| &mut self.cells[index])
+    }
+
+    // Returns whether the position was in the grid.
+    pub fn set(&mut self, position: Position, edge: Edge, cell: T) -> bool {
+        match self.get_mut(position, edge) {
+            None => false,
+            Some(c) => {
+                *c = cell;
+                true
+            },
+        }
+    }
+
+    // The positions of the neighbors of a cell, resolved with the given edge. With a bounded edge, neighbors outside of
+    // the grid are left out. With a clamped edge, they're the cell itself or its neighbors on the edge, so the same
+    // position can come up more than once.
+    pub fn neighbors(&self, position: Position, neighborhood: Neighborhood, edge: Edge)
+                     -> impl Iterator<Item = Position> + '_ {
+        neighborhood.offsets().iter().filter_map(move |(dx, dy)| self.resolve((position.0 + dx, position.1 + dy), edge))
+    }
+
+    // Every position in the grid, row after row.
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x as i64, y as i64)))
+    }
+
+    // Every cell with its position, row after row.
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    // Writes the grid back as a map, with a line per row.
+    pub fn render(&self, cell: impl Fn(&T) -> char) -> String {
+        let mut text = String::new();
+        for row in self.cells.chunks(self.width.max(1)) {
+            text.extend(row.iter().map(&cell));
+            text.push('\n');
+        }
+        text
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(|line| line.to_string()).collect()
+    }
+
+    fn parse(text: &str) -> Result<Grid<bool>, ParseError> {
+        Grid::parse(&lines(text), "'.' or '#'", |c| match c { '.' => Some(false), '#' => Some(true), _ => None })
+    }
+
+    fn render(grid: &Grid<bool>) -> String {
+        grid.render(|cell| if *cell { '#' } else { '.' })
+    }
+
+    const MAP: &str = "\
+#..
+.#.
+..#
+##.
+";
+
+    #[test]
+    fn parses_and_renders() {
+        let grid = parse(MAP).unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 4));
+        assert_eq!(grid.get((1, 1), Edge::Bounded), Some(&true));
+        assert_eq!(grid.get((2, 1), Edge::Bounded), Some(&false));
+        assert_eq!(render(&grid), MAP);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = parse("#..\n.x.").unwrap_err();
+        assert_eq!((error.line_index, error.columns, error.expected.as_str()), (1, 1..2, "'.' or '#'"));
+        let error = parse("#..\n.#..").unwrap_err();
+        assert_eq!((error.line_index, error.columns, error.found.as_str()), (1, 3..4, "4 cells"));
+        let error = parse("#..\n.#").unwrap_err();
+        assert_eq!((error.line_index, error.columns, error.found.as_str()), (1, 2..2, "2 cells"));
+        let error = parse("#..\n\n").unwrap_err();
+        assert_eq!((error.line_index, error.found.as_str()), (1, "0 cells"));
+        assert_eq!(parse("").unwrap_err().found, "end of input");
+    }
+
+    #[test]
+    fn edges() {
+        let grid = parse(MAP).unwrap();
+        assert_eq!(grid.get((3, 0), Edge::Bounded), None);
+        assert_eq!(grid.get((-1, 0), Edge::Bounded), None);
+        assert_eq!(grid.resolve((4, -1), Edge::Wrap), Some((1, 3)));
+        assert_eq!(grid.resolve((-7, 9), Edge::Wrap), Some((2, 1)));
+        assert_eq!(grid.resolve((5, -2), Edge::Clamp), Some((2, 0)));
+        assert_eq!(Grid::new(0, 0, false).get((0, 0), Edge::Wrap), None);
+    }
+
+    #[test]
+    fn set_cells() {
+        let mut grid = Grid::new(2, 2, false);
+        assert!(grid.set((3, 0), Edge::Wrap, true));
+        assert!(!grid.set((3, 0), Edge::Bounded, true));
+        assert_eq!(render(&grid), ".#\n..\n");
+    }
+
+    #[test]
+    fn neighbors() {
+        let grid = Grid::new(3, 3, false);
+        let count = |position, neighborhood, edge| grid.neighbors(position, neighborhood, edge).count();
+        assert_eq!(count((1, 1), Neighborhood::Orthogonal, Edge::Bounded), 4);
+        assert_eq!(count((1, 1), Neighborhood::All, Edge::Bounded), 8);
+        assert_eq!(count((1, 1), Neighborhood::Hex, Edge::Bounded), 6);
+        assert_eq!(count((0, 0), Neighborhood::Orthogonal, Edge::Bounded), 2);
+        assert_eq!(count((0, 0), Neighborhood::All, Edge::Bounded), 3);
+        assert_eq!(count((0, 0), Neighborhood::Hex, Edge::Bounded), 2);
+        assert_eq!(count((0, 0), Neighborhood::All, Edge::Wrap), 8);
+        assert_eq!(count((0, 0), Neighborhood::All, Edge::Clamp), 8);
+        let hex: Vec<Position> = grid.neighbors((1, 1), Neighborhood::Hex, Edge::Bounded).collect();
+        assert_eq!(hex, [(1, 0), (2, 0), (2, 1), (1, 2), (0, 2), (0, 1)]);
+        let wrapped: Vec<Position> = grid.neighbors((0, 0), Neighborhood::Orthogonal, Edge::Wrap).collect();
+        assert_eq!(wrapped, [(0, 2), (1, 0), (0, 1), (2, 0)]);
+    }
+
+    #[test]
+    fn iterates_row_after_row() {
+        let grid = parse(MAP).unwrap();
+        let trees: Vec<Position> = grid.iter().filter(|(_, cell)| **cell).map(|(position, _)| position).collect();
+        assert_eq!(trees, [(0, 0), (1, 1), (2, 2), (0, 3), (1, 3)]);
+    }
+
+}
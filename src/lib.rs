@@ -0,0 +1,12 @@
+pub mod bench;
+pub mod check;
+pub mod days;
+pub mod events;
+pub mod generate;
+pub mod grid;
+pub mod math;
+pub mod parse;
+pub mod report;
+pub mod runner;
+pub mod scaffold;
+pub mod solve;
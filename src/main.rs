@@ -1,122 +1,191 @@
 use std::fmt;
-use std::io::{self, BufRead};
-use std::io::Write;
-mod days;
-use days::CumulativeSolver;
-use days::FixedLineCountSolver;
+use std::io::{self, BufRead, IsTerminal, Write};
+use aoc2020::bench::{self, Baseline, Short};
+use aoc2020::check::{self, Status};
+use aoc2020::days;
+use aoc2020::events::{self, Event};
+use aoc2020::generate;
+use aoc2020::report::{self, Format, Report};
+use aoc2020::runner::{self, RunError};
+use aoc2020::scaffold::{self, Kind};
+use aoc2020::solve::{Cancellation, Progress};
+
+const USAGE: &str = "\
+Usage: aoc2020 <command> [options]
+       aoc2020 <day> <part> [<input> [<output>]]
+       aoc2020 new-day <day> [options]
+
+Commands:
+  run      Solve one part of one day, and write the answer to the output file.
+  check    Solve every part of every day, and compare with the stored answers.
+  list     List the available solvers.
+  bench    Time solvers over many runs, reading and solving separately.
+  generate Write a random input for a day, for stress testing.
+  new-day  Start a new day: its module with a test stub, an empty input, and its
+           registration. Run from the repository root.
+  help     Show this text.
+
+Options for every command:
+  -y, --year <year>      The event whose solvers to use, with its inputs in
+                         inputs/<year>/ and answers in outputs/<year>/. Defaults
+                         to 2020.
+
+Options for run:
+  -d, --day <day>        The day to solve, between 1 and 25.
+  -p, --part <part>      The part of the day to solve. Without it, every part of
+                         the day is solved from a single read of the input.
+  -i, --input <path>     The input file, or - for standard input.
+                         Defaults to inputs/<year>/day_<day>.txt.
+  -o, --output <path>    The output file, or - for standard output.
+                         Defaults to outputs/<year>/day_<day>-part_<part>.txt, and can't be
+                         given when solving both parts.
+  -n, --no-output        Print the answer without writing an output file.
+  -f, --format <format>  How to print the outcome: text, or json for one object
+                         per part with the answer or error, the parse and solve
+                         times, and the number of input lines used. Defaults to
+                         text. With json, an output of - is left out, since the
+                         answer is in the object.
+  -t, --time-limit <s>   Cancel any part that takes longer than this many
+                         seconds, and report it as timed out. Defaults to none.
+  -h, --help             Show this text.
+
+Options for check:
+  -f, --format <format>  text, or json for one object per part. Defaults to text.
+  -j, --jobs <count>     How many solvers to run at once, or 0 for one per CPU.
+                         Results are listed in the same order either way.
+                         Defaults to 1.
+  -t, --time-limit <s>   Cancel any solver that takes longer than this many
+                         seconds, and report it as timed out. Defaults to none.
+
+Options for bench:
+  -d, --day <day>            Only time the solvers of this day.
+  -p, --part <part>          Only time the solvers of this part.
+  --iterations <count>       How many times to run each solver. Defaults to 10.
+  --save-baseline <path>     Save the median times, to compare later runs against.
+                             Times already in the file for other solvers are kept.
+  --baseline <path>          Compare the median times with a saved baseline.
+  --threshold <percent>      How much slower than the baseline counts as a
+                             regression. Defaults to 10.
+
+Options for generate:
+  -d, --day <day>        The day to make an input for.
+  --size <size>          How big to make the input, in entries, rows, groups or
+                         whatever the day's input is made of. Defaults to about
+                         the size of a real input.
+  --seed <seed>          The same seed always gives the same input. Defaults to 1.
+  -o, --output <path>    The file to write, or - for standard output, which is
+                         the default.
+
+Options for new-day:
+  --kind <kind>          cumulative, for solvers that take the input a line at a
+                         time, or whole-input, for solvers that need all of it at
+                         once. Defaults to cumulative.
+";
 
 struct Parameters {
+    year: u32,
     day_index: u32,
     part_index: u32,
     input_file_path: std::path::PathBuf,
     output_file_path: std::path::PathBuf,
+    write_output: bool,
+    format: Format,
+    time_limit: Option<std::time::Duration>,
 }
 
-struct Runner<'a> {
-    input_file_path: &'a std::path::Path,
-    output_file_path: &'a std::path::Path,
-    input_file: &'a std::fs::File,
-    output_file: &'a mut std::fs::File,
+// Both parts of a day, where the answers always go to their conventional paths.
+struct DayParameters {
+    year: u32,
+    day_index: u32,
+    input_file_path: std::path::PathBuf,
+    write_output: bool,
+    format: Format,
+    time_limit: Option<std::time::Duration>,
 }
 
-pub trait Runnable {
-    fn run_cumulative<S: CumulativeSolver>(&mut self, solver: &mut S);
-    fn run_fixed_line_count<S: FixedLineCountSolver>(&mut self);
+struct BenchParameters {
+    year: u32,
+    day_index: Option<u32>,
+    part_index: Option<u32>,
+    iterations: usize,
+    save_baseline_path: Option<std::path::PathBuf>,
+    baseline_path: Option<std::path::PathBuf>,
+    threshold_percent: f64,
 }
 
-impl Runnable for Runner<'_> {
-    fn run_cumulative<S: CumulativeSolver>(&mut self, solver: &mut S) {
-        let mut output = None;
-        for (line_index, line_result) in io::BufReader::new(self.input_file).lines().enumerate() {
-            match line_result {
-                Err(re) => {
-                    println!("Read error for file {}: {}", self.input_file_path.display(), re);
-                    std::process::exit(1);
-                },
-                Ok(line) => {
-                    match solver.deserialize(&line) {
-                        None => {
-                            println!("{}:{}: Failed to parse line.",
-                                     self.input_file_path.display(),
-                                     line_index);
-                            std::process::exit(1);
-                        },
-                        Some(input) => {
-                            output = solver.accumulate(input);
-                            if output.is_some() {
-                                break;
-                            }
-                        },
-                    }
-                },
-            };
-        }
-        match output.or(solver.solve()) {
-            None => {
-                println!("Failed to solve: invalid input.");
-                std::process::exit(1);
-            },
-            Some(output) => {
-                match self.output_file.write_fmt(format_args!("{}\n", solver.serialize(&output))) {
-                    Ok(()) => (),
-                    Err(we) => {
-                        println!("Write error for file {}: {}",
-                                 self.output_file_path.display(), we);
-                        std::process::exit(1);
-                    },
-                }
-            }
-        }
-    }
+struct CheckParameters {
+    year: u32,
+    format: Format,
+    job_count: usize,
+    time_limit: Option<std::time::Duration>,
+}
 
-    fn run_fixed_line_count<S: FixedLineCountSolver>(&mut self) {
-        let mut lines = Vec::new();
-        for line_result in io::BufReader::new(self.input_file).lines() {
-            match line_result {
-                Err(re) => {
-                    println!("Read error for file {}: {}", self.input_file_path.display(), re);
-                    std::process::exit(1);
-                },
-                Ok(line) => {
-                    lines.push(line);
-                },
-            }
-        }
-        match S::from_input(&lines) {
-            None => {
-                println!("Invalid input.");
-                std::process::exit(1);
-            },
-            Some(mut solver) => {
-                match solver.solve() {
-                    None => {
-                        println!("Failed to solve.");
-                        std::process::exit(1);
-                    },
-                    Some(result) => {
-                        println!("{}", result);
-                        match self.output_file.write_fmt(format_args!("{}\n", result.to_string())) {
-                            Ok(()) => (),
-                            Err(we) => {
-                                println!("Write error for file {}: {}",
-                                         self.output_file_path.display(), we);
-                                std::process::exit(1);
-                            },
-                        }
-                    }
-                }
-            },
-        }
-    }
+struct GenerateParameters {
+    year: u32,
+    day_index: u32,
+    size: Option<usize>,
+    seed: u64,
+    output_file_path: std::path::PathBuf,
+}
+
+struct NewDayParameters {
+    year: u32,
+    day_index: u32,
+    kind: Kind,
+}
+
+enum Command {
+    Run(Parameters),
+    RunDay(DayParameters),
+    Bench(BenchParameters),
+    Generate(GenerateParameters),
+    NewDay(NewDayParameters),
+    Check(CheckParameters),
+    List(u32),
+    Help,
+}
+
+#[derive(Default)]
+struct Options {
+    year: Option<u32>,
+    day_index: Option<u32>,
+    part_index: Option<u32>,
+    input_file_path: Option<std::path::PathBuf>,
+    output_file_path: Option<std::path::PathBuf>,
+    no_output: bool,
+    iterations: Option<usize>,
+    save_baseline_path: Option<std::path::PathBuf>,
+    baseline_path: Option<std::path::PathBuf>,
+    threshold_percent: Option<f64>,
+    format: Option<Format>,
+    job_count: Option<usize>,
+    time_limit: Option<std::time::Duration>,
+    size: Option<usize>,
+    seed: Option<u64>,
+    kind: Option<Kind>,
+    help: bool,
 }
 
 enum ReadArgumentsError {
+    NoCommandGiven,
+    UnknownCommand(String),
+    UnknownOption(String),
+    NoOptionValueGiven(String),
+    UnexpectedArgument(String),
+    YearInvalid(String, std::num::ParseIntError),
     NoDayIndexGiven,
     DayIndexInvalid(String, std::num::ParseIntError),
     NoPartIndexGiven,
     PartIndexInvalid(String, std::num::ParseIntError),
-    NoInputFilePathGiven,
-    NoOutputFilePathGiven
+    OutputFilePathForBothParts,
+    IterationCountInvalid(String, std::num::ParseIntError),
+    ThresholdInvalid(String, std::num::ParseFloatError),
+    FormatInvalid(String),
+    JobCountInvalid(String, std::num::ParseIntError),
+    TimeLimitInvalid(String),
+    SizeInvalid(String, std::num::ParseIntError),
+    SeedInvalid(String, std::num::ParseIntError),
+    KindInvalid(String),
 }
 
 enum ParametersError {
@@ -126,12 +195,29 @@ enum ParametersError {
 impl fmt::Display for ReadArgumentsError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            ReadArgumentsError::NoCommandGiven => write!(f, "No command given."),
+            ReadArgumentsError::UnknownCommand(s) => write!(f, "Unknown command: '{}'.", s),
+            ReadArgumentsError::UnknownOption(s) => write!(f, "Unknown option: '{}'.", s),
+            ReadArgumentsError::NoOptionValueGiven(s) => write!(f, "No value given for option '{}'.", s),
+            ReadArgumentsError::UnexpectedArgument(s) => write!(f, "Unexpected argument: '{}'.", s),
+            ReadArgumentsError::YearInvalid(s, e) => write!(f, "Year invalid: '{}': {}.", s, e),
             ReadArgumentsError::NoDayIndexGiven => write!(f, "No day index given."),
             ReadArgumentsError::DayIndexInvalid(s, e) => write!(f, "Day index invalid: '{}': {}.", s, e),
             ReadArgumentsError::NoPartIndexGiven => write!(f, "No part index given."),
             ReadArgumentsError::PartIndexInvalid(s, e) => write!(f, "Part index invalid: '{}': {}.", s, e),
-            ReadArgumentsError::NoInputFilePathGiven => write!(f, "No input file path given."),
-            ReadArgumentsError::NoOutputFilePathGiven => write!(f, "No output file path given."),
+            ReadArgumentsError::OutputFilePathForBothParts =>
+                write!(f, "An output file can only be given when solving a single part."),
+            ReadArgumentsError::IterationCountInvalid(s, e) => write!(f, "Iteration count invalid: '{}': {}.", s, e),
+            ReadArgumentsError::ThresholdInvalid(s, e) => write!(f, "Threshold invalid: '{}': {}.", s, e),
+            ReadArgumentsError::FormatInvalid(s) =>
+                write!(f, "Format invalid: '{}': should be text or json.", s),
+            ReadArgumentsError::JobCountInvalid(s, e) => write!(f, "Job count invalid: '{}': {}.", s, e),
+            ReadArgumentsError::TimeLimitInvalid(s) =>
+                write!(f, "Time limit invalid: '{}': should be a positive number of seconds.", s),
+            ReadArgumentsError::SizeInvalid(s, e) => write!(f, "Size invalid: '{}': {}.", s, e),
+            ReadArgumentsError::SeedInvalid(s, e) => write!(f, "Seed invalid: '{}': {}.", s, e),
+            ReadArgumentsError::KindInvalid(s) =>
+                write!(f, "Kind invalid: '{}': should be cumulative or whole-input.", s),
         }
     }
 }
@@ -140,142 +226,733 @@ impl fmt::Display for ParametersError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             ParametersError::DayIndexOutOfBounds(i) =>
-                write!(f, "Day index out of bounds: '{}': should be between 1 and 24.", i),
+                write!(f, "Day index out of bounds: '{}': should be between 1 and 25.", i),
         }
     }
 }
 
+fn parse_index(s: String, invalid: fn(String, std::num::ParseIntError) -> ReadArgumentsError)
+               -> Result<u32, ReadArgumentsError> {
+    match s.parse() {
+        Err(e) => Err(invalid(s, e)),
+        Ok(i) => Ok(i)
+    }
+}
 
-fn read_arguments() -> Result<Parameters, ReadArgumentsError> {
-    let day_index: u32 =
-        match std::env::args().nth(1) {
-            None => return Err(ReadArgumentsError::NoDayIndexGiven),
-            Some(s) => {
-                match s.parse() {
-                    Err(e) => return Err(ReadArgumentsError::DayIndexInvalid(s.clone(), e)),
-                    Ok(i) => i
-                }
-            }
+// The options each command uses, by their long names. Every command also takes --year and --help.
+const RUN_OPTIONS: [&str; 7] = ["--day", "--part", "--input", "--output", "--no-output", "--format", "--time-limit"];
+const CHECK_OPTIONS: [&str; 3] = ["--format", "--jobs", "--time-limit"];
+const LIST_OPTIONS: [&str; 0] = [];
+const BENCH_OPTIONS: [&str; 6] = ["--day", "--part", "--iterations", "--save-baseline", "--baseline", "--threshold"];
+const GENERATE_OPTIONS: [&str; 4] = ["--day", "--size", "--seed", "--output"];
+const NEW_DAY_OPTIONS: [&str; 2] = ["--day", "--kind"];
+
+// The long name of an option, given either of its names, or nothing if there's no such option.
+fn long_option_name(name: &str) -> Option<&'static str> {
+    let long_name =
+        match name {
+            "-y" | "--year" => "--year",
+            "-d" | "--day" => "--day",
+            "-p" | "--part" => "--part",
+            "-i" | "--input" => "--input",
+            "-o" | "--output" => "--output",
+            "-n" | "--no-output" => "--no-output",
+            "--iterations" => "--iterations",
+            "--save-baseline" => "--save-baseline",
+            "--baseline" => "--baseline",
+            "--threshold" => "--threshold",
+            "-f" | "--format" => "--format",
+            "-j" | "--jobs" => "--jobs",
+            "-t" | "--time-limit" => "--time-limit",
+            "--size" => "--size",
+            "--seed" => "--seed",
+            "--kind" => "--kind",
+            "-h" | "--help" => "--help",
+            _ => return None,
         };
-    let part_index: u32 =
-        match std::env::args().nth(2) {
-            None => return Err(ReadArgumentsError::NoPartIndexGiven),
-            Some(s) => {
-                match s.parse() {
-                    Err(e) => return Err(ReadArgumentsError::PartIndexInvalid(s.clone(), e)),
-                    Ok(i) => i
-                }
-            }
+    Some(long_name)
+}
+
+// Reads options given either as "--name value" or as "--name=value".
+// An option that exists, but isn't one the command uses, is an unexpected argument.
+fn read_options(arguments: Vec<String>, command_options: &[&str]) -> Result<Options, ReadArgumentsError> {
+    let mut options = Options::default();
+    let mut arguments = arguments.into_iter();
+    while let Some(argument) = arguments.next() {
+        let (name, inline_value) =
+            match argument.split_once('=') {
+                Some((name, value)) if name.starts_with("--") => (name, Some(value.to_string())),
+                _ => (argument.as_str(), None),
+            };
+        let long_name =
+            match long_option_name(name) {
+                None if name.starts_with('-') => return Err(ReadArgumentsError::UnknownOption(argument)),
+                None => return Err(ReadArgumentsError::UnexpectedArgument(argument)),
+                Some(long_name) => long_name,
+            };
+        if !matches!(long_name, "--year" | "--help") && !command_options.contains(&long_name) {
+            return Err(ReadArgumentsError::UnexpectedArgument(argument));
+        }
+        let mut value = || {
+            inline_value.clone().or_else(|| arguments.next())
+                .ok_or_else(|| ReadArgumentsError::NoOptionValueGiven(name.to_string()))
         };
-    let input_file_path =
-        match std::env::args().nth(3) {
-            None => return Err(ReadArgumentsError::NoInputFilePathGiven),
-            Some(s) => std::path::PathBuf::from(s)
+        match long_name {
+            "--year" => {
+                let s = value()?;
+                options.year = Some(s.parse().map_err(|e| ReadArgumentsError::YearInvalid(s, e))?);
+            },
+            "--day" =>
+                options.day_index = Some(parse_index(value()?, ReadArgumentsError::DayIndexInvalid)?),
+            "--part" =>
+                options.part_index = Some(parse_index(value()?, ReadArgumentsError::PartIndexInvalid)?),
+            "--input" =>
+                options.input_file_path = Some(std::path::PathBuf::from(value()?)),
+            "--output" =>
+                options.output_file_path = Some(std::path::PathBuf::from(value()?)),
+            "--no-output" =>
+                options.no_output = true,
+            "--iterations" => {
+                let s = value()?;
+                options.iterations = Some(s.parse().map_err(|e| ReadArgumentsError::IterationCountInvalid(s, e))?);
+            },
+            "--save-baseline" =>
+                options.save_baseline_path = Some(std::path::PathBuf::from(value()?)),
+            "--baseline" =>
+                options.baseline_path = Some(std::path::PathBuf::from(value()?)),
+            "--threshold" => {
+                let s = value()?;
+                options.threshold_percent = Some(s.parse().map_err(|e| ReadArgumentsError::ThresholdInvalid(s, e))?);
+            },
+            "--format" =>
+                options.format =
+                    match value()?.as_str() {
+                        "text" => Some(Format::Text),
+                        "json" => Some(Format::Json),
+                        s => return Err(ReadArgumentsError::FormatInvalid(s.to_string())),
+                    },
+            "--jobs" => {
+                let s = value()?;
+                options.job_count = Some(s.parse().map_err(|e| ReadArgumentsError::JobCountInvalid(s, e))?);
+            },
+            "--time-limit" => {
+                let s = value()?;
+                let time_limit =
+                    s.parse().ok().filter(|seconds: &f64| *seconds > 0.0)
+                        .and_then(|seconds| std::time::Duration::try_from_secs_f64(seconds).ok());
+                options.time_limit = Some(time_limit.ok_or(ReadArgumentsError::TimeLimitInvalid(s))?);
+            },
+            "--size" => {
+                let s = value()?;
+                options.size = Some(s.parse().map_err(|e| ReadArgumentsError::SizeInvalid(s, e))?);
+            },
+            "--seed" => {
+                let s = value()?;
+                options.seed = Some(s.parse().map_err(|e| ReadArgumentsError::SeedInvalid(s, e))?);
+            },
+            "--kind" => {
+                let s = value()?;
+                options.kind = Some(Kind::from_name(&s).ok_or(ReadArgumentsError::KindInvalid(s))?);
+            },
+            "--help" =>
+                options.help = true,
+            _ => unreachable!("every long option name is read"),
+        }
+    }
+    Ok(options)
+}
+
+// Reads the old style of arguments: day, part, and optionally the input and output paths.
+fn read_positional_arguments(arguments: Vec<String>) -> Result<Options, ReadArgumentsError> {
+    let mut arguments = arguments.into_iter();
+    let day_index = arguments.next().ok_or(ReadArgumentsError::NoDayIndexGiven)?;
+    let part_index = arguments.next().ok_or(ReadArgumentsError::NoPartIndexGiven)?;
+    let options =
+        Options {
+            year: None,
+            day_index: Some(parse_index(day_index, ReadArgumentsError::DayIndexInvalid)?),
+            part_index: Some(parse_index(part_index, ReadArgumentsError::PartIndexInvalid)?),
+            input_file_path: arguments.next().map(std::path::PathBuf::from),
+            output_file_path: arguments.next().map(std::path::PathBuf::from),
+            no_output: false,
+            iterations: None,
+            save_baseline_path: None,
+            baseline_path: None,
+            threshold_percent: None,
+            format: None,
+            job_count: None,
+            time_limit: None,
+            size: None,
+            seed: None,
+            kind: None,
+            help: false,
         };
-    let output_file_path =
-        match std::env::args().nth(4) {
-            None => return Err(ReadArgumentsError::NoOutputFilePathGiven),
-            Some(s) => std::path::PathBuf::from(s)
+    match arguments.next() {
+        Some(argument) => Err(ReadArgumentsError::UnexpectedArgument(argument)),
+        None => Ok(options),
+    }
+}
+
+impl Options {
+    // Fills in the conventional paths for whatever wasn't given.
+    fn into_parameters(self) -> Result<Parameters, ReadArgumentsError> {
+        let day_index = self.day_index.ok_or(ReadArgumentsError::NoDayIndexGiven)?;
+        let part_index = self.part_index.ok_or(ReadArgumentsError::NoPartIndexGiven)?;
+        let year = self.year.unwrap_or(events::DEFAULT_YEAR);
+        Ok(
+            Parameters {
+                year,
+                day_index,
+                part_index,
+                input_file_path: self.input_file_path.unwrap_or_else(|| runner::input_file_path(year, day_index)),
+                output_file_path:
+                    self.output_file_path.unwrap_or_else(|| runner::output_file_path(year, day_index, part_index)),
+                write_output: !self.no_output,
+                format: self.format.unwrap_or(Format::Text),
+                time_limit: self.time_limit,
+            }
+        )
+    }
+}
+
+impl Options {
+    // Standard output is allowed, since that's where both answers get printed anyway.
+    fn into_day_parameters(self) -> Result<DayParameters, ReadArgumentsError> {
+        let day_index = self.day_index.ok_or(ReadArgumentsError::NoDayIndexGiven)?;
+        let write_output =
+            match &self.output_file_path {
+                None => !self.no_output,
+                Some(path) if is_standard_stream(path) => false,
+                Some(_) => return Err(ReadArgumentsError::OutputFilePathForBothParts),
+            };
+        let year = self.year.unwrap_or(events::DEFAULT_YEAR);
+        Ok(
+            DayParameters {
+                year,
+                day_index,
+                input_file_path: self.input_file_path.unwrap_or_else(|| runner::input_file_path(year, day_index)),
+                write_output,
+                format: self.format.unwrap_or(Format::Text),
+                time_limit: self.time_limit,
+            }
+        )
+    }
+}
+
+impl Options {
+    fn into_check_parameters(self) -> CheckParameters {
+        CheckParameters {
+            year: self.year.unwrap_or(events::DEFAULT_YEAR),
+            format: self.format.unwrap_or(Format::Text),
+            job_count:
+                match self.job_count {
+                    None => 1,
+                    Some(0) => check::available_job_count(),
+                    Some(n) => n,
+                },
+            time_limit: self.time_limit,
+        }
+    }
+}
+
+impl Options {
+    fn into_generate_parameters(self) -> Result<GenerateParameters, ReadArgumentsError> {
+        Ok(
+            GenerateParameters {
+                year: self.year.unwrap_or(events::DEFAULT_YEAR),
+                day_index: self.day_index.ok_or(ReadArgumentsError::NoDayIndexGiven)?,
+                size: self.size,
+                seed: self.seed.unwrap_or(1),
+                output_file_path: self.output_file_path.unwrap_or_else(|| std::path::PathBuf::from("-")),
+            }
+        )
+    }
+}
+
+impl Options {
+    fn into_new_day_parameters(self) -> Result<NewDayParameters, ReadArgumentsError> {
+        Ok(
+            NewDayParameters {
+                year: self.year.unwrap_or(events::DEFAULT_YEAR),
+                day_index: self.day_index.ok_or(ReadArgumentsError::NoDayIndexGiven)?,
+                kind: self.kind.unwrap_or(Kind::Cumulative),
+            }
+        )
+    }
+}
+
+impl Options {
+    fn into_bench_parameters(self) -> BenchParameters {
+        BenchParameters {
+            year: self.year.unwrap_or(events::DEFAULT_YEAR),
+            day_index: self.day_index,
+            part_index: self.part_index,
+            iterations: self.iterations.unwrap_or(10),
+            save_baseline_path: self.save_baseline_path,
+            baseline_path: self.baseline_path,
+            threshold_percent: self.threshold_percent.unwrap_or(10.0),
+        }
+    }
+}
+
+fn read_arguments() -> Result<Command, ReadArgumentsError> {
+    let mut arguments: Vec<String> = std::env::args().skip(1).collect();
+    if arguments.is_empty() {
+        return Err(ReadArgumentsError::NoCommandGiven);
+    }
+    let command = arguments.remove(0);
+    if command.parse::<u32>().is_ok() {
+        arguments.insert(0, command);
+        return Ok(Command::Run(read_positional_arguments(arguments)?.into_parameters()?));
+    }
+    let options =
+        match command.as_str() {
+            "help" | "-h" | "--help" => return Ok(Command::Help),
+            "run" => read_options(arguments, &RUN_OPTIONS)?,
+            "check" => read_options(arguments, &CHECK_OPTIONS)?,
+            "list" => read_options(arguments, &LIST_OPTIONS)?,
+            "bench" => read_options(arguments, &BENCH_OPTIONS)?,
+            "generate" => read_options(arguments, &GENERATE_OPTIONS)?,
+            // The day is given by position, like "new-day 16".
+            "new-day" => {
+                if arguments.first().is_some_and(|argument| !argument.starts_with('-')) {
+                    arguments.insert(0, "--day".to_string());
+                }
+                read_options(arguments, &NEW_DAY_OPTIONS)?
+            },
+            _ => return Err(ReadArgumentsError::UnknownCommand(command)),
         };
-    Ok(
-        Parameters {
-            day_index: day_index,
-            part_index: part_index,
-            input_file_path: input_file_path,
-            output_file_path: output_file_path
+    if options.help {
+        return Ok(Command::Help);
+    }
+    match command.as_str() {
+        "run" if options.day_index.is_some() && options.part_index.is_none() =>
+            Ok(Command::RunDay(options.into_day_parameters()?)),
+        "run" => Ok(Command::Run(options.into_parameters()?)),
+        "check" => Ok(Command::Check(options.into_check_parameters())),
+        "bench" => Ok(Command::Bench(options.into_bench_parameters())),
+        "generate" => Ok(Command::Generate(options.into_generate_parameters()?)),
+        "new-day" => Ok(Command::NewDay(options.into_new_day_parameters()?)),
+        _ => Ok(Command::List(options.year.unwrap_or(events::DEFAULT_YEAR))),
+    }
+}
+
+fn validate_day_index(day_index: u32) -> Option<ParametersError> {
+    if !(1..=25).contains(&day_index) {
+        return Some(ParametersError::DayIndexOutOfBounds(day_index));
+    }
+    None
+}
+
+fn open_input(path: &std::path::Path) -> Box<dyn BufRead> {
+    if is_standard_stream(path) {
+        return Box::new(io::stdin().lock());
+    }
+    match std::fs::File::open(path) {
+        Err(e) => {
+            println!("Failed to open input file '{}': {}.", path.display(), e);
+            std::process::exit(1);
+        },
+        Ok(f) => Box::new(io::BufReader::new(f))
+    }
+}
+
+// Reads all of the input at once, for a solver that runs on a thread of its own.
+fn read_input(input: &mut dyn BufRead, input_file_path: &std::path::Path) -> String {
+    match io::read_to_string(input) {
+        Err(e) => {
+            let e = RunError::Read(e);
+            report(&e, input_file_path, std::path::Path::new("-"));
+            std::process::exit(e.exit_code());
+        },
+        Ok(s) => s
+    }
+}
+
+fn create_output_file(file_path: &std::path::Path) -> std::fs::File {
+    match file_path.parent() {
+        Some(directory_path) if !std::path::Path::is_dir(directory_path) => {
+            if let Err(cde) = std::fs::create_dir_all(directory_path) {
+                println!("The directory '{}' does not exist, and could not be created: {}.",
+                         directory_path.display(), cde);
+                std::process::exit(1);
+            }
+        },
+        _ => (),
+    };
+
+    match std::fs::File::create(file_path) {
+        Err(ce) => {
+            println!("Failed to open output file '{}': {}.", file_path.display(), ce);
+            std::process::exit(1);
+        },
+        Ok(f) => f
+    }
+}
+
+fn report(e: &RunError, input_file_path: &std::path::Path, output_file_path: &std::path::Path) {
+    match e {
+        RunError::Read(re) =>
+            println!("Read error for file {}: {}", input_file_path.display(), re),
+        RunError::Parse(pe) =>
+            println!("{}:{}", input_file_path.display(), pe),
+        RunError::Write(we) =>
+            println!("Write error for file {}: {}", output_file_path.display(), we),
+        RunError::Solve(_) =>
+            println!("{}", e),
+    }
+}
+
+// A path of "-" stands for standard input or standard output.
+fn is_standard_stream(path: &std::path::Path) -> bool {
+    path.as_os_str() == "-"
+}
+
+fn find_event(year: u32) -> &'static Event {
+    match events::find(year) {
+        None => {
+            let years: Vec<String> = events::EVENTS.iter().map(|event| event.year.to_string()).collect();
+            println!("No solvers for {}. Available years: {}.", year, years.join(", "));
+            std::process::exit(1);
+        },
+        Some(event) => event
+    }
+}
+
+fn print_solvers(registry: &days::Registry) {
+    for registration in registry.registrations() {
+        println!("  day {} part {}: {} ({})",
+                 registration.day_index, registration.part_index,
+                 registration.title, registration.kind);
+    }
+}
+
+// Progress is only drawn for someone watching a terminal, and never mixed in with JSON.
+fn with_progress<T>(format: Format, label: &str, progress: &Progress, work: impl FnOnce() -> T) -> T {
+    if format == Format::Text && io::stderr().is_terminal() {
+        runner::with_progress_line(label, progress, work)
+    } else {
+        work()
+    }
+}
+
+fn run_check(parameters: CheckParameters) -> ! {
+    let registry = (find_event(parameters.year).registry)();
+    let progress = Progress::new();
+    let checks =
+        with_progress(parameters.format, "Checking", &progress, || {
+            check::check_all(&registry, parameters.job_count, parameters.time_limit, &progress)
+        });
+    let failed = checks.iter().filter(|c| c.regressed()).count();
+    if parameters.format == Format::Json {
+        for c in &checks {
+            println!("{}", c.to_json());
         }
-    )
+        std::process::exit(if failed > 0 { 1 } else { 0 });
+    }
+    println!("{:>3} {:>4}  {:<24} status", "day", "part", "title");
+    for c in &checks {
+        println!("{:>3} {:>4}  {:<24} {}", c.day_index, c.part_index, c.title, c.status);
+    }
+    let passed = checks.iter().filter(|c| matches!(c.status, Status::Pass(_))).count();
+    let skipped = checks.iter().filter(|c| matches!(c.status, Status::Skipped(_))).count();
+    println!("{} passed, {} failed, {} skipped.", passed, failed, skipped);
+    std::process::exit(if failed > 0 { 1 } else { 0 });
 }
 
-impl Parameters {
-    fn validate(&self) -> Option<ParametersError> {
-        if self.day_index < 1 || self.day_index > 24 {
-            return Some(ParametersError::DayIndexOutOfBounds(self.day_index));
+fn run_bench(parameters: BenchParameters) -> ! {
+    let registry = (find_event(parameters.year).registry)();
+    let baseline =
+        parameters.baseline_path.as_ref().map(|path| {
+            match Baseline::load(path) {
+                Err(e) => {
+                    println!("Failed to load baseline '{}': {}.", path.display(), e);
+                    std::process::exit(1);
+                },
+                Ok(b) => b
+            }
+        });
+    let registrations: Vec<&days::Registration> =
+        registry.registrations().iter()
+            .filter(|r| parameters.day_index.is_none_or(|d| d == r.day_index))
+            .filter(|r| parameters.part_index.is_none_or(|p| p == r.part_index))
+            .collect();
+    if registrations.is_empty() {
+        println!("No solvers to time. Available solvers:");
+        print_solvers(&registry);
+        std::process::exit(1);
+    }
+
+    println!("{:>3} {:>4}  {:<5} {:>10} {:>10} {:>10} {:>10}", "day", "part", "phase", "min", "median", "mean", "p95");
+    let mut measurements = Vec::new();
+    let mut failed = 0;
+    let mut regressed = 0;
+    for registration in registrations {
+        let input_file_path = runner::input_file_path(parameters.year, registration.day_index);
+        let measurement =
+            match std::fs::read_to_string(&input_file_path) {
+                Err(e) => {
+                    println!("{:>3} {:>4}  Failed to read '{}': {}.",
+                             registration.day_index, registration.part_index, input_file_path.display(), e);
+                    failed += 1;
+                    continue;
+                },
+                Ok(input) => bench::measure(registration, &input, parameters.iterations),
+            };
+        let measurement =
+            match measurement {
+                Err(e) => {
+                    println!("{:>3} {:>4}  {}", registration.day_index, registration.part_index, e);
+                    failed += 1;
+                    continue;
+                },
+                Ok(m) => m
+            };
+        let baseline_medians =
+            baseline.as_ref()
+                .and_then(|b| b.medians(parameters.year, measurement.day_index, measurement.part_index));
+        let phases = [("parse", &measurement.parse, baseline_medians.map(|m| m.0)),
+                      ("solve", &measurement.solve, baseline_medians.map(|m| m.1))];
+        for (phase, statistics, baseline_median) in phases.iter() {
+            let comparison =
+                match baseline_median {
+                    None => String::new(),
+                    Some(baseline_median) => {
+                        let change = bench::change_percent(*baseline_median, statistics.median);
+                        if change > parameters.threshold_percent {
+                            regressed += 1;
+                            format!("  {:+.1}% REGRESSED", change)
+                        } else {
+                            format!("  {:+.1}%", change)
+                        }
+                    },
+                };
+            println!("{:>3} {:>4}  {:<5} {:>10} {:>10} {:>10} {:>10}{}",
+                     measurement.day_index, measurement.part_index, phase,
+                     Short(statistics.min), Short(statistics.median), Short(statistics.mean), Short(statistics.p95),
+                     comparison);
         }
-        None
+        measurements.push(measurement);
     }
+
+    if let Some(path) = &parameters.save_baseline_path {
+        if let Err(e) = Baseline::save(parameters.year, &measurements, path) {
+            println!("Failed to save baseline '{}': {}.", path.display(), e);
+            std::process::exit(1);
+        }
+    }
+    if regressed > 0 {
+        println!("{} phases regressed by more than {}%.", regressed, parameters.threshold_percent);
+    }
+    std::process::exit(if failed > 0 || regressed > 0 { 1 } else { 0 });
 }
 
-fn main() {
-    let parameters =
-        match read_arguments() {
-            Err(e) => {
-                println!("Invalid arguments: {}", e);
+// Writes the answer of a successful run, turning the report into a write error if that fails.
+fn write_answer(report: &mut Report, output: &mut dyn Write) {
+    if let Ok(answer) = &report.result {
+        if let Err(e) = writeln!(output, "{}", answer).and_then(|_| output.flush()) {
+            report.result = Err(RunError::Write(e));
+        }
+    }
+}
+
+fn run_generate(parameters: GenerateParameters) {
+    let generator =
+        match generate::find(parameters.day_index).filter(|_| parameters.year == generate::YEAR) {
+            None => {
+                let day_indices: Vec<String> = generate::GENERATORS.iter().map(|g| g.day_index.to_string()).collect();
+                println!("No input generator for day {} of {}. Available days of {}: {}.",
+                         parameters.day_index, parameters.year, generate::YEAR, day_indices.join(", "));
                 std::process::exit(1);
             },
-            Ok(p) =>
-                match p.validate() {
-                    Some(e) => {
-                        println!("Invalid parameters: {}", e);
-                        std::process::exit(1);
-                    },
-                    None => p
-                }
+            Some(g) => g
         };
-    let input_file = {
-        let path = parameters.input_file_path.as_path();
-        match std::fs::File::open(path) {
-            Err(e) => {
-                println!("Failed to open input file '{}': {}.", path.display(), e);
+    let size = parameters.size.unwrap_or(generator.default_size);
+    let input = (generator.generate)(&mut generate::Random::new(parameters.seed), size);
+    let result =
+        if is_standard_stream(&parameters.output_file_path) {
+            io::stdout().write_all(input.as_bytes())
+        } else {
+            create_output_file(&parameters.output_file_path).write_all(input.as_bytes())
+        };
+    if let Err(e) = result {
+        println!("Write error for file {}: {}", parameters.output_file_path.display(), e);
+        std::process::exit(RunError::Write(e).exit_code());
+    }
+}
+
+fn run_new_day(parameters: NewDayParameters) {
+    if let Some(e) = validate_day_index(parameters.day_index) {
+        println!("{}", e);
+        std::process::exit(1);
+    }
+    let event = find_event(parameters.year);
+    match scaffold::new_day(std::path::Path::new("."), event, parameters.day_index, parameters.kind) {
+        Err(e) => {
+            println!("{}", e);
+            std::process::exit(1);
+        },
+        Ok(scaffold) => {
+            println!("Created {}", scaffold.module_file_path.display());
+            println!("Created {}, for the puzzle input", scaffold.input_file_path.display());
+            println!("Registered day {} in {}", parameters.day_index, scaffold.registry_file_path.display());
+        },
+    }
+}
+
+fn run(parameters: Parameters) {
+    let registry = (find_event(parameters.year).registry)();
+    let registration =
+        match registry.find(parameters.day_index, parameters.part_index) {
+            None => {
+                println!("Solver not implemented for day {} part {}. Available solvers:",
+                         parameters.day_index, parameters.part_index);
+                print_solvers(&registry);
                 std::process::exit(1);
             },
-            Ok(f) => f
+            Some(r) => r
+        };
+    let mut input = open_input(&parameters.input_file_path);
+    let progress = Progress::new();
+    let label = format!("Day {} part {}", parameters.day_index, parameters.part_index);
+    let mut run_report =
+        with_progress(parameters.format, &label, &progress, || {
+            match parameters.time_limit {
+                None => report::run_with(registration, &mut (&mut input).lines(), Cancellation::new(), progress.clone()),
+                Some(time_limit) => {
+                    let input = read_input(&mut input, &parameters.input_file_path);
+                    report::run_with_time_limit(registration, input, time_limit, progress.clone())
+                },
+            }
+        });
+    let writes_to_standard_output = parameters.write_output && is_standard_stream(&parameters.output_file_path);
+    if run_report.result.is_ok() && parameters.write_output {
+        if !writes_to_standard_output {
+            write_answer(&mut run_report, &mut create_output_file(&parameters.output_file_path));
+        } else if parameters.format == Format::Text {
+            write_answer(&mut run_report, &mut io::stdout());
         }
-    };
-    let mut output_file = {
-        let file_path = parameters.output_file_path.as_path();
-        match file_path.parent() {
-            None => (),
-            Some(directory_path) => {
-                if !std::path::Path::is_dir(directory_path) {
-                    match std::fs::create_dir_all(directory_path) {
-                        Err(cde) => {
-                            println!("The directory '{}' does not exist, and could not be created: {}.",
-                                     directory_path.display(), cde);
-                            std::process::exit(1);
-                        },
-                        Ok(()) => (),
-                    }
-                }
+    }
+
+    if parameters.format == Format::Json {
+        println!("{}", run_report.to_json());
+    }
+    match &run_report.result {
+        Ok(answer) => {
+            if parameters.format == Format::Text && !writes_to_standard_output {
+                println!("{}", answer);
             }
-        };
+        },
+        Err(e) => {
+            if parameters.format == Format::Text {
+                report(e, &parameters.input_file_path, &parameters.output_file_path);
+            }
+            std::process::exit(e.exit_code());
+        },
+    }
 
-        match std::fs::File::create(file_path) {
-            Err(ce) => {
-                println!("Failed to open output file '{}': {}.", file_path.display(), ce);
-                std::process::exit(1);
+}
+
+fn run_day(parameters: DayParameters) {
+    let registry = (find_event(parameters.year).registry)();
+    let day_registration = registry.find_day(parameters.day_index);
+    // Without a solver for both parts, each registered part is solved on its own.
+    let registrations: Vec<&days::Registration> =
+        registry.registrations().iter().filter(|r| r.day_index == parameters.day_index).collect();
+    if day_registration.is_none() && registrations.is_empty() {
+        println!("Solver not implemented for day {}. Available solvers:", parameters.day_index);
+        print_solvers(&registry);
+        std::process::exit(1);
+    }
+    let mut input = open_input(&parameters.input_file_path);
+    let progress = Progress::new();
+    let label = format!("Day {}", parameters.day_index);
+    let reports =
+        with_progress(parameters.format, &label, &progress, || {
+            match (day_registration, parameters.time_limit) {
+                (Some(day_registration), None) =>
+                    report::run_day(day_registration, &mut (&mut input).lines(), Cancellation::new(), progress.clone()),
+                (None, None) => report::run_parts(&registrations, &mut (&mut input).lines(), progress.clone()),
+                (Some(day_registration), Some(time_limit)) => {
+                    let input = read_input(&mut input, &parameters.input_file_path);
+                    report::run_day_with_time_limit(day_registration, input, time_limit, progress.clone())
+                },
+                (None, Some(time_limit)) => {
+                    let input = read_input(&mut input, &parameters.input_file_path);
+                    registrations.iter().map(|registration| {
+                        report::run_with_time_limit(registration, input.clone(), time_limit, progress.clone())
+                    }).collect()
+                },
+            }
+        });
+    // Parts read by the same solver fail to read in the same way, so that's only reported once.
+    if parameters.format == Format::Text {
+        let shared_read = day_registration.is_some();
+        match &reports[0].result {
+            Err(e @ RunError::Read(_)) => {
+                report(e, &parameters.input_file_path, std::path::Path::new("-"));
+                std::process::exit(e.exit_code());
             },
-            Ok(f) => f
+            Err(e @ RunError::Parse(_)) if shared_read => {
+                report(e, &parameters.input_file_path, std::path::Path::new("-"));
+                std::process::exit(e.exit_code());
+            },
+            _ => (),
         }
-    };
+    }
+    let mut exit_code = 0;
+    for mut part_report in reports {
+        let output_file_path = runner::output_file_path(parameters.year, parameters.day_index, part_report.part_index);
+        if parameters.write_output {
+            write_answer(&mut part_report, &mut create_output_file(&output_file_path));
+        }
+        match (&part_report.result, parameters.format) {
+            (_, Format::Json) => println!("{}", part_report.to_json()),
+            (Ok(answer), Format::Text) => println!("Part {}: {}", part_report.part_index, answer),
+            (Err(e), Format::Text) => {
+                print!("Part {}: ", part_report.part_index);
+                report(e, &parameters.input_file_path, &output_file_path);
+            },
+        }
+        if let Err(e) = &part_report.result {
+            if exit_code == 0 {
+                exit_code = e.exit_code();
+            }
+        }
+    }
+    std::process::exit(exit_code);
+}
 
-    let mut runner =
-        Runner {
-            input_file: &input_file,
-            input_file_path: parameters.input_file_path.as_path(),
-            output_file: &mut output_file,
-            output_file_path: parameters.output_file_path.as_path()
+fn main() {
+    let command =
+        match read_arguments() {
+            Err(e) => {
+                println!("Invalid arguments: {}", e);
+                println!("Run 'aoc2020 help' for usage.");
+                std::process::exit(1);
+            },
+            Ok(c) => c
         };
-    match (parameters.day_index, parameters.part_index) {
-        (1, 1) => runner.run_cumulative(&mut days::day_1::part_1::State::new()),
-        (1, 2) => runner.run_cumulative(&mut days::day_1::part_2::State::new()),
-        (2, 1) => runner.run_cumulative(&mut days::day_2::part_1::State::new()),
-        (2, 2) => runner.run_cumulative(&mut days::day_2::part_2::State::new()),
-        (3, 1) => runner.run_cumulative(&mut days::day_3::part_1::State::new()),
-        (3, 2) => runner.run_cumulative(&mut days::day_3::part_2::State::new()),
-        (6, 1) => runner.run_cumulative(&mut days::day_6::part_1::State::new()),
-        (6, 2) => runner.run_cumulative(&mut days::day_6::part_2::State::new()),
-        (8, 1) => runner.run_cumulative(&mut days::day_8::part_1::State::new()),
-        (8, 2) => runner.run_cumulative(&mut days::day_8::part_2::State::new()),
-        (12, 1) => runner.run_cumulative(&mut days::day_12::part_1::State::new()),
-        (13, 1) => runner.run_fixed_line_count::<days::day_13::part_1::Instance>(),
-        (15, 1) => runner.run_fixed_line_count::<days::day_15::part_1::Instance>(),
-        (15, 2) => runner.run_fixed_line_count::<days::day_15::part_2::Instance>(),
-        _ => {
-            println!("Solver not implemented for day {} part {}.",
-                     parameters.day_index, parameters.part_index);
-            std::process::exit(1);
+    match command {
+        Command::Help => print!("{}", USAGE),
+        Command::List(year) => print_solvers(&(find_event(year).registry)()),
+        Command::Check(parameters) => run_check(parameters),
+        Command::Bench(parameters) => run_bench(parameters),
+        Command::Generate(parameters) => run_generate(parameters),
+        Command::NewDay(parameters) => run_new_day(parameters),
+        Command::Run(parameters) => {
+            if let Some(e) = validate_day_index(parameters.day_index) {
+                println!("Invalid parameters: {}", e);
+                std::process::exit(1);
+            }
+            run(parameters);
+        },
+        Command::RunDay(parameters) => {
+            if let Some(e) = validate_day_index(parameters.day_index) {
+                println!("Invalid parameters: {}", e);
+                std::process::exit(1);
+            }
+            run_day(parameters);
         },
     }
-
 }
@@ -0,0 +1,293 @@
+use std::fmt;
+
+pub fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+// Nothing if it overflows. The lcm with 0 is 0.
+pub fn lcm(a: u128, b: u128) -> Option<u128> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+// Returns the gcd g, which is never negative, along with x and y such that a*x + b*y = g.
+// The coefficients are never larger than the inputs, so only an input of i128::MIN can overflow.
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut r0, mut r1) = (a, b);
+    let (mut x0, mut x1) = (1, 0);
+    let (mut y0, mut y1) = (0, 1);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (x0, x1) = (x1, x0 - q * x1);
+        (y0, y1) = (y1, y0 - q * y1);
+    }
+    if r0 < 0 {
+        (-r0, -x0, -y0)
+    } else {
+        (r0, x0, y0)
+    }
+}
+
+// Adds a number below the modulus to one that's at most the modulus, without overflowing.
+fn add_mod(a: u128, b: u128, modulus: u128) -> u128 {
+    if a >= modulus - b {
+        a - (modulus - b)
+    } else {
+        a + b
+    }
+}
+
+// The smallest number that takes a up to a multiple of the modulus. Nothing if the modulus is 0.
+pub fn checked_neg_mod(a: u128, modulus: u128) -> Option<u128> {
+    a.checked_rem(modulus).map(|r| if r == 0 { 0 } else { modulus - r })
+}
+
+// Nothing if the modulus is 0. The product is taken in 128 bits, so it can't overflow.
+pub fn checked_mul_mod(a: u64, b: u64, modulus: u64) -> Option<u64> {
+    if modulus == 0 {
+        return None;
+    }
+    Some(((a as u128 * b as u128) % modulus as u128) as u64)
+}
+
+// Nothing if the modulus is 0. Products that don't fit in 128 bits are done by doubling and adding.
+pub fn checked_mul_mod_u128(a: u128, b: u128, modulus: u128) -> Option<u128> {
+    if modulus == 0 {
+        return None;
+    }
+    let (mut a, mut b) = (a % modulus, b % modulus);
+    if let Some(product) = a.checked_mul(b) {
+        return Some(product % modulus);
+    }
+    let mut product = 0;
+    while b > 0 {
+        if b & 1 == 1 {
+            product = add_mod(product, a, modulus);
+        }
+        a = add_mod(a, a, modulus);
+        b >>= 1;
+    }
+    Some(product)
+}
+
+// The x below the modulus such that a*x is 1 modulo it, or nothing if a and the modulus aren't coprime.
+// Coefficients are kept modulo the modulus, so there are no signs, and nothing overflows.
+pub fn mod_inverse(a: u128, modulus: u128) -> Option<u128> {
+    if modulus == 0 {
+        return None;
+    }
+    let (mut r0, mut r1) = (modulus, a % modulus);
+    let (mut t0, mut t1) = (0, 1 % modulus);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        let qt1 = checked_mul_mod_u128(q, t1, modulus)?;
+        (t0, t1) = (t1, add_mod(t0, modulus - qt1, modulus));
+    }
+    if r0 == 1 {
+        Some(t0)
+    } else {
+        None
+    }
+}
+
+// The numbers x where x = remainder modulo the modulus.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Congruence {
+    pub remainder: u128,
+    pub modulus: u128,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CrtError {
+    ZeroModulus,
+    // Moduli with a common factor can ask for different remainders modulo it, so no number satisfies both.
+    Inconsistent(Congruence, Congruence),
+    // The lcm of the moduli doesn't fit in 128 bits.
+    Overflow,
+}
+
+impl fmt::Display for CrtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CrtError::ZeroModulus => write!(f, "a modulus is 0"),
+            CrtError::Inconsistent(a, b) =>
+                write!(f, "no number is {} modulo {} and {} modulo {}", a.remainder, a.modulus, b.remainder, b.modulus),
+            CrtError::Overflow => write!(f, "the lcm of the moduli overflows 128 bits"),
+        }
+    }
+}
+
+// Combines two congruences into one, modulo the lcm of their moduli.
+fn combine(a: Congruence, b: Congruence) -> Result<Congruence, CrtError> {
+    let g = gcd(a.modulus, b.modulus);
+    let modulus = (a.modulus / g).checked_mul(b.modulus).ok_or(CrtError::Overflow)?;
+    let (r1, r2) = (a.remainder % a.modulus, b.remainder % b.modulus);
+    // The solutions are r1 + a.modulus*k, and k has to make up the difference to r2, modulo b.modulus.
+    let difference = add_mod(r2, b.modulus - r1 % b.modulus, b.modulus);
+    if !difference.is_multiple_of(g) {
+        return Err(CrtError::Inconsistent(a, b));
+    }
+    let reduced_modulus = b.modulus / g;
+    // The moduli are coprime once their gcd is divided out, so there's always an inverse.
+    let inverse = mod_inverse((a.modulus / g) % reduced_modulus, reduced_modulus).ok_or(CrtError::Overflow)?;
+    let k = checked_mul_mod_u128(difference / g, inverse, reduced_modulus).ok_or(CrtError::Overflow)?;
+    // Both are below the lcm, since k is below b.modulus / g.
+    let remainder = r1 + a.modulus * k;
+    Ok(
+        Congruence {
+            remainder,
+            modulus,
+        }
+    )
+}
+
+// The smallest remainder and the modulus of the numbers that satisfy every congruence. Moduli don't have to be
+// coprime, as long as the congruences agree where they overlap. No congruences at all are satisfied by every number.
+pub fn chinese_remainder(congruences: &[Congruence]) -> Result<Congruence, CrtError> {
+    let mut solution = Congruence { remainder: 0, modulus: 1 };
+    for congruence in congruences {
+        if congruence.modulus == 0 {
+            return Err(CrtError::ZeroModulus);
+        }
+        solution = combine(solution, *congruence)?;
+    }
+    Ok(solution)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn congruence(remainder: u128, modulus: u128) -> Congruence {
+        Congruence { remainder, modulus }
+    }
+
+    #[test]
+    fn gcd_and_lcm() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(0, 7), 7);
+        assert_eq!(gcd(7, 0), 7);
+        assert_eq!(gcd(17, 5), 1);
+        assert_eq!(lcm(4, 6), Some(12));
+        assert_eq!(lcm(0, 6), Some(0));
+        assert_eq!(lcm(u128::MAX, u128::MAX), Some(u128::MAX));
+        assert_eq!(lcm(u128::MAX, 2), None);
+    }
+
+    #[test]
+    fn extended_gcd_coefficients() {
+        for a in -30..=30 {
+            for b in -30..=30 {
+                let (g, x, y) = extended_gcd(a, b);
+                assert_eq!(g, gcd(a.unsigned_abs(), b.unsigned_abs()) as i128, "gcd of {} and {}", a, b);
+                assert_eq!(a * x + b * y, g, "coefficients of {} and {}", a, b);
+            }
+        }
+        let (g, x, y) = extended_gcd(i64::MAX as i128, i64::MIN as i128);
+        assert_eq!((g, i64::MAX as i128 * x + i64::MIN as i128 * y), (1, 1));
+    }
+
+    #[test]
+    fn mul_mod() {
+        assert_eq!(checked_mul_mod(u64::MAX, u64::MAX, 1_000_000_007), Some(((u64::MAX as u128).pow(2) % 1_000_000_007) as u64));
+        assert_eq!(checked_mul_mod(3, 4, 0), None);
+        assert_eq!(checked_mul_mod_u128(3, 4, 0), None);
+        // Both are 1 modulo u128::MAX - 1.
+        assert_eq!(checked_mul_mod_u128(u128::MAX, u128::MAX, u128::MAX - 1), Some(1));
+        // 2^127 is -1 modulo 2^127 + 1.
+        let modulus = (1 << 127) + 1;
+        assert_eq!(checked_mul_mod_u128(1 << 127, 1 << 127, modulus), Some(1));
+        assert_eq!(checked_mul_mod_u128(1 << 127, 3, modulus), Some(modulus - 3));
+        for a in [0, 1, 2, 12345, u64::MAX as u128] {
+            for b in [0, 1, 3, 67890, u64::MAX as u128] {
+                assert_eq!(checked_mul_mod_u128(a, b, 1_000_000_007), Some(a * b % 1_000_000_007));
+            }
+        }
+    }
+
+    #[test]
+    fn negations() {
+        assert_eq!(checked_neg_mod(939, 59), Some(5));
+        assert_eq!(checked_neg_mod(939, 7), Some(6));
+        assert_eq!(checked_neg_mod(14, 7), Some(0));
+        assert_eq!(checked_neg_mod(0, 7), Some(0));
+        assert_eq!(checked_neg_mod(3, 1), Some(0));
+        assert_eq!(checked_neg_mod(u128::MAX, u128::MAX - 1), Some(u128::MAX - 2));
+        assert_eq!(checked_neg_mod(5, 0), None);
+    }
+
+    #[test]
+    fn inverses() {
+        for modulus in 1..40 {
+            for a in 0..(2 * modulus) {
+                match mod_inverse(a, modulus) {
+                    None => assert_ne!(gcd(a, modulus), 1, "{} has an inverse modulo {}", a, modulus),
+                    Some(x) => {
+                        assert!(x < modulus);
+                        assert_eq!(a * x % modulus, 1 % modulus, "inverse of {} modulo {}", a, modulus);
+                    },
+                }
+            }
+        }
+        assert_eq!(mod_inverse(3, 0), None);
+        let modulus = u128::MAX;
+        let x = mod_inverse(2, modulus).unwrap();
+        assert_eq!(checked_mul_mod_u128(2, x, modulus), Some(1));
+    }
+
+    // The bus schedule from day 13, where bus i departs i minutes after the timestamp.
+    #[test]
+    fn bus_schedule() {
+        let ids = [(0, 7), (1, 13), (4, 59), (6, 31), (7, 19)];
+        let congruences: Vec<Congruence> = ids.iter().map(|(i, id)| congruence((id - i % id) % id, *id)).collect();
+        assert_eq!(chinese_remainder(&congruences), Ok(congruence(1068781, 7 * 13 * 59 * 31 * 19)));
+    }
+
+    #[test]
+    fn non_coprime_moduli() {
+        assert_eq!(chinese_remainder(&[congruence(2, 4), congruence(4, 6)]), Ok(congruence(10, 12)));
+        assert_eq!(chinese_remainder(&[congruence(1, 4), congruence(2, 6)]),
+                   Err(CrtError::Inconsistent(congruence(1, 4), congruence(2, 6))));
+        // Every pair of small congruences, against a search.
+        for m1 in 1..10 {
+            for m2 in 1..10 {
+                let modulus = lcm(m1, m2).unwrap();
+                for r1 in 0..m1 {
+                    for r2 in 0..m2 {
+                        let expected = (0..modulus).find(|x| x % m1 == r1 && x % m2 == r2);
+                        let result = chinese_remainder(&[congruence(r1, m1), congruence(r2, m2)]);
+                        match expected {
+                            None => assert!(result.is_err(), "{} mod {} and {} mod {}", r1, m1, r2, m2),
+                            Some(x) => assert_eq!(result, Ok(congruence(x, modulus))),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn large_moduli() {
+        // Two primes just below 2^61 and 2^64, whose product needs 125 bits.
+        let (p, q) = ((1 << 61) - 1, u64::MAX as u128 - 58);
+        let solution = chinese_remainder(&[congruence(5, p), congruence(7, q)]).unwrap();
+        assert_eq!((solution.remainder % p, solution.remainder % q, solution.modulus), (5, 7, p * q));
+        assert_eq!(chinese_remainder(&[congruence(0, p), congruence(0, q), congruence(0, 64)]), Err(CrtError::Overflow));
+    }
+
+    #[test]
+    fn edge_cases() {
+        assert_eq!(chinese_remainder(&[]), Ok(congruence(0, 1)));
+        assert_eq!(chinese_remainder(&[congruence(3, 0)]), Err(CrtError::ZeroModulus));
+        assert_eq!(chinese_remainder(&[congruence(17, 5)]), Ok(congruence(2, 5)));
+    }
+
+}
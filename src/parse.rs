@@ -0,0 +1,90 @@
+use std::fmt;
+use std::ops::Range;
+
+// Describes what is wrong with the input, and where.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    pub line_index: usize,
+    // For input made of groups of lines, the group the offending line is in.
+    pub group_index: Option<usize>,
+    // Byte offsets into the offending line.
+    pub columns: Range<usize>,
+    // The offending line.
+    pub text: String,
+    pub expected: String,
+    pub found: String,
+}
+
+impl ParseError {
+
+    // An error for the given columns of a line, where the found text is whatever is in those columns.
+    pub fn new(text: &str, columns: Range<usize>, expected: &str) -> Self {
+        let found =
+            match text.get(columns.clone()) {
+                None | Some("") => "end of line".to_string(),
+                Some(s) => format!("'{}'", s),
+            };
+        ParseError {
+            line_index: 0,
+            group_index: None,
+            columns,
+            text: text.to_string(),
+            expected: expected.to_string(),
+            found,
+        }
+    }
+
+    pub fn with_found(mut self, found: &str) -> Self {
+        self.found = found.to_string();
+        self
+    }
+
+    pub fn on_line(mut self, line_index: usize) -> Self {
+        self.line_index = line_index;
+        self
+    }
+
+    pub fn in_group(mut self, group_index: usize) -> Self {
+        self.group_index = Some(group_index);
+        self
+    }
+
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: ", self.line_index + 1, self.columns.start + 1)?;
+        if let Some(group_index) = self.group_index {
+            write!(f, "in group {}: ", group_index + 1)?;
+        }
+        writeln!(f, "expected {}, found {}.", self.expected, self.found)?;
+        writeln!(f, "    {}", self.text)?;
+        let width = std::cmp::max(1, self.columns.end.saturating_sub(self.columns.start));
+        write!(f, "    {}{}", " ".repeat(self.columns.start), "^".repeat(width))
+    }
+}
+
+// Splits a line at the separator, also giving the offset of each part.
+pub fn split_with_offsets(line: &str, separator: char) -> impl Iterator<Item = (usize, &str)> {
+    line.split(separator).scan(0, move |offset, part| {
+        let part_offset = *offset;
+        *offset += part.len() + separator.len_utf8();
+        Some((part_offset, part))
+    })
+}
+
+// Checks that the input has exactly the given number of lines.
+pub fn expect_line_count(lines: &[String], count: usize) -> Result<(), ParseError> {
+    if lines.len() == count {
+        return Ok(());
+    }
+    let line_count = |n| if n == 1 { "1 line".to_string() } else { format!("{} lines", n) };
+    let expected = line_count(count);
+    let found = line_count(lines.len());
+    let error =
+        match lines.get(count) {
+            Some(extra_line) => ParseError::new(extra_line, 0..extra_line.len(), &expected).on_line(count),
+            None => ParseError::new("", 0..0, &expected).on_line(lines.len()),
+        };
+    Err(error.with_found(&found))
+}
@@ -0,0 +1,474 @@
+use std::fmt;
+use std::io;
+use std::panic;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
+use crate::days::{DayRegistration, Registration, DAY_PART_COUNT};
+use crate::runner::RunError;
+use crate::solve::{Cancellation, Progress, SolveError};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+// How long a run took, and how much of the input it used. Whatever a run didn't get to, or wasn't waited for, is
+// left out.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Timings {
+    pub parse: Option<Duration>,
+    pub solve: Option<Duration>,
+    pub line_count: Option<usize>,
+}
+
+// The outcome of running one solver once.
+pub struct Report {
+    pub day_index: u32,
+    pub part_index: u32,
+    pub title: &'static str,
+    pub result: Result<String, RunError>,
+    pub timings: Timings,
+}
+
+// Reads the lines into a fresh solver and solves it, timing each phase.
+pub fn run(registration: &Registration, lines: &mut dyn Iterator<Item = io::Result<String>>) -> Report {
+    run_with(registration, lines, Cancellation::new(), Progress::new())
+}
+
+// Like run, but the solver can be cancelled, and reports how far along it is.
+pub fn run_with(registration: &Registration, lines: &mut dyn Iterator<Item = io::Result<String>>,
+                cancellation: Cancellation, progress: Progress) -> Report {
+    let mut solver = (registration.solver)();
+    solver.set_cancellation(cancellation);
+    solver.set_progress(progress);
+    let mut timings = Timings::default();
+    let parse_start = Instant::now();
+    let read_result = solver.read(lines);
+    timings.parse = Some(parse_start.elapsed());
+    let result =
+        read_result.and_then(|line_count| {
+            timings.line_count = Some(line_count);
+            let solve_start = Instant::now();
+            let result = solver.solve();
+            timings.solve = Some(solve_start.elapsed());
+            result
+        });
+    Report {
+        day_index: registration.day_index,
+        part_index: registration.part_index,
+        title: registration.title,
+        result,
+        timings,
+    }
+}
+
+// Like run, but on its own thread, and gives up once the time limit is reached.
+// The solver is then cancelled, and left to stop by itself if it polls for that, or to run
+// until the process exits if it doesn't. A panic in the solver is passed on to the caller.
+pub fn run_with_time_limit(registration: &Registration, input: String, time_limit: Duration,
+                           progress: Progress) -> Report {
+    let registration = *registration;
+    let parts = [(registration.day_index, registration.part_index, registration.title)];
+    let mut reports =
+        with_time_limit(&parts, time_limit, move |cancellation, send| {
+            let mut lines = input.lines().map(|line| Ok(line.to_string()));
+            send(run_with(&registration, &mut lines, cancellation, progress));
+        });
+    reports.remove(0)
+}
+
+// Like run_day, but on its own thread, where each part gets the time limit to itself, counting the read in the
+// first part. Parts answered before the limit was reached keep their answers.
+pub fn run_day_with_time_limit(day_registration: &DayRegistration, input: String, time_limit: Duration,
+                               progress: Progress) -> Vec<Report> {
+    let day_registration = *day_registration;
+    let parts: Vec<(u32, u32, &'static str)> =
+        (1..=DAY_PART_COUNT).map(|part_index| (day_registration.day_index, part_index, day_registration.title)).collect();
+    with_time_limit(&parts, time_limit, move |cancellation, send| {
+        let mut lines = input.lines().map(|line| Ok(line.to_string()));
+        solve_day(&day_registration, &mut lines, cancellation, progress, send);
+    })
+}
+
+// Runs the work on its own thread, which sends a report for each of the given parts, in order, as soon as it's done.
+// Each part gets the time limit from when the one before it was done. Once a part runs out of time, the work is
+// cancelled, and that part and the ones after it are reported as timed out.
+fn with_time_limit(parts: &[(u32, u32, &'static str)], time_limit: Duration,
+                   work: impl FnOnce(Cancellation, &mut dyn FnMut(Report)) + Send + 'static) -> Vec<Report> {
+    let cancellation = Cancellation::new();
+    let thread_cancellation = cancellation.clone();
+    let (sender, receiver) = mpsc::channel();
+    let handle =
+        std::thread::spawn(move || {
+            work(thread_cancellation, &mut |report| {
+                let _ = sender.send(report);
+            });
+        });
+    let mut reports = Vec::new();
+    let mut start = Instant::now();
+    for &(day_index, part_index, title) in parts {
+        let received =
+            if cancellation.is_cancelled() {
+                Err(RecvTimeoutError::Timeout)
+            } else {
+                receiver.recv_timeout(time_limit.saturating_sub(start.elapsed()))
+            };
+        match received {
+            Ok(report) => {
+                reports.push(report);
+                start = Instant::now();
+            },
+            Err(RecvTimeoutError::Disconnected) =>
+                match handle.join() {
+                    Err(payload) => panic::resume_unwind(payload),
+                    Ok(()) => unreachable!("the solver thread finished without sending a report for every part"),
+                },
+            Err(RecvTimeoutError::Timeout) => {
+                cancellation.cancel();
+                // The read happens on the solver's thread, so all that's known is how long the part was waited for.
+                let reason = format!("timed out after {:?}", time_limit);
+                reports.push(
+                    Report {
+                        day_index,
+                        part_index,
+                        title,
+                        result: Err(RunError::Solve(SolveError::Cancelled(reason))),
+                        timings: Timings { solve: Some(start.elapsed()), ..Timings::default() },
+                    }
+                );
+            },
+        }
+    }
+    reports
+}
+
+// Like run, but for both parts of a day from a single read, giving one report per part.
+// Each report gets the time it took to read the shared input.
+pub fn run_day(day_registration: &DayRegistration, lines: &mut dyn Iterator<Item = io::Result<String>>,
+               cancellation: Cancellation, progress: Progress) -> Vec<Report> {
+    let mut reports = Vec::new();
+    solve_day(day_registration, lines, cancellation, progress, &mut |report| reports.push(report));
+    reports
+}
+
+// Hands over the report of each part as soon as it's done.
+fn solve_day(day_registration: &DayRegistration, lines: &mut dyn Iterator<Item = io::Result<String>>,
+             cancellation: Cancellation, progress: Progress, send: &mut dyn FnMut(Report)) {
+    let mut solver = (day_registration.solver)();
+    solver.set_cancellation(cancellation);
+    solver.set_progress(progress);
+    let parse_start = Instant::now();
+    let read_result = solver.read(lines);
+    let parse_time = parse_start.elapsed();
+    for part_index in 1..=DAY_PART_COUNT {
+        let mut timings = Timings { parse: Some(parse_time), ..Timings::default() };
+        let result =
+            match &read_result {
+                Err(e) => Err(duplicate(e)),
+                Ok(line_count) => {
+                    timings.line_count = Some(*line_count);
+                    let solve_start = Instant::now();
+                    let result = solver.solve(part_index);
+                    timings.solve = Some(solve_start.elapsed());
+                    result
+                },
+            };
+        send(
+            Report {
+                day_index: day_registration.day_index,
+                part_index,
+                title: day_registration.title,
+                result,
+                timings,
+            }
+        );
+    }
+}
+
+// For a day without a solver for both parts, runs each of its parts on its own, from a single read of the input.
+// A read error is given to every part.
+pub fn run_parts(registrations: &[&Registration], lines: &mut dyn Iterator<Item = io::Result<String>>,
+                 progress: Progress) -> Vec<Report> {
+    let lines: Result<Vec<String>, RunError> = lines.collect::<io::Result<_>>().map_err(RunError::Read);
+    registrations.iter().map(|registration| {
+        match &lines {
+            Err(e) =>
+                Report {
+                    day_index: registration.day_index,
+                    part_index: registration.part_index,
+                    title: registration.title,
+                    result: Err(duplicate(e)),
+                    timings: Timings::default(),
+                },
+            Ok(lines) =>
+                run_with(registration, &mut lines.iter().map(|line| Ok(line.clone())), Cancellation::new(),
+                         progress.clone()),
+        }
+    }).collect()
+}
+
+// A read error is shared by every part, but io::Error can't be cloned, so it's rebuilt from its kind and text.
+fn duplicate(e: &RunError) -> RunError {
+    match e {
+        RunError::Read(re) => RunError::Read(io::Error::new(re.kind(), re.to_string())),
+        RunError::Parse(pe) => RunError::Parse(pe.clone()),
+        RunError::Solve(se) => RunError::Solve(se.clone()),
+        RunError::Write(we) => RunError::Write(io::Error::new(we.kind(), we.to_string())),
+    }
+}
+
+impl Report {
+    pub fn to_json(&self) -> JsonObject {
+        let object =
+            JsonObject::new()
+                .number("day", self.day_index)
+                .number("part", self.part_index)
+                .string("title", self.title);
+        let object =
+            match &self.result {
+                Ok(answer) => object.string("status", "solved").string("answer", answer).null("error"),
+                Err(e) => object.string("status", status(e)).null("answer").object("error", error_to_json(e)),
+            };
+        object.timings(&self.timings)
+    }
+}
+
+// A short name for the kind of failure, in the same order as the exit codes.
+pub fn status(e: &RunError) -> &'static str {
+    match e {
+        RunError::Read(_) => "read_error",
+        RunError::Parse(_) => "parse_error",
+        RunError::Solve(SolveError::NoSolution(_)) => "no_solution",
+        RunError::Solve(SolveError::PreconditionViolated(_)) => "precondition_violated",
+        RunError::Solve(SolveError::LimitExceeded(_)) => "limit_exceeded",
+        RunError::Write(_) => "write_error",
+        RunError::Solve(SolveError::Cancelled(_)) => "cancelled",
+    }
+}
+
+// Parse errors also give where they happened, with groups, lines and columns counted from 1. The columns run from
+// column up to but not including end_column, so an empty span has both the same.
+pub fn error_to_json(e: &RunError) -> JsonObject {
+    let object = JsonObject::new().string("message", &e.to_string()).number("exit_code", e.exit_code());
+    match e {
+        RunError::Parse(pe) => {
+            let object =
+                match pe.group_index {
+                    None => object,
+                    Some(group_index) => object.number("group", group_index + 1),
+                };
+            object
+                .number("line", pe.line_index + 1)
+                .number("column", pe.columns.start + 1)
+                .number("end_column", pe.columns.end + 1)
+                .string("text", &pe.text)
+                .string("expected", &pe.expected)
+                .string("found", &pe.found)
+        },
+        _ => object,
+    }
+}
+
+// Just enough JSON to write flat reports, with fields kept in the order they're added.
+pub struct JsonObject {
+    fields: Vec<(&'static str, String)>,
+}
+
+impl JsonObject {
+
+    pub fn new() -> Self {
+        JsonObject {
+            fields: Vec::new(),
+        }
+    }
+
+    // Adding a field that's already there replaces its value, but keeps its place.
+    fn with(mut self, name: &'static str, value: String) -> Self {
+        match self.fields.iter_mut().find(|(n, _)| *n == name) {
+            Some(field) => field.1 = value,
+            None => self.fields.push((name, value)),
+        }
+        self
+    }
+
+    pub fn string(self, name: &'static str, value: &str) -> Self {
+        self.with(name, quote(value))
+    }
+
+    pub fn number<N: fmt::Display>(self, name: &'static str, value: N) -> Self {
+        self.with(name, value.to_string())
+    }
+
+    pub fn null(self, name: &'static str) -> Self {
+        self.with(name, "null".to_string())
+    }
+
+    pub fn number_or_null<N: fmt::Display>(self, name: &'static str, value: Option<N>) -> Self {
+        match value {
+            Some(value) => self.number(name, value),
+            None => self.null(name),
+        }
+    }
+
+    pub fn object(self, name: &'static str, value: JsonObject) -> Self {
+        self.with(name, value.to_string())
+    }
+
+    // Times are whole nanoseconds, so nothing is lost to rounding. What wasn't measured is null, rather than 0.
+    pub fn timings(self, timings: &Timings) -> Self {
+        self.number_or_null("parse_time_ns", timings.parse.map(|parse| parse.as_nanos()))
+            .number_or_null("solve_time_ns", timings.solve.map(|solve| solve.as_nanos()))
+            .number_or_null("input_lines", timings.line_count)
+    }
+
+}
+
+impl Default for JsonObject {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for JsonObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{")?;
+        for (field_index, (name, value)) in self.fields.iter().enumerate() {
+            if field_index > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}:{}", quote(name), value)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+fn quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::days::{stubs, Registry};
+    use crate::parse::ParseError;
+
+    fn lines(input: &str) -> impl Iterator<Item = io::Result<String>> + '_ {
+        input.lines().map(|line| Ok(line.to_string()))
+    }
+
+    #[test]
+    fn parts_share_one_read() {
+        let registry = Registry::new();
+        let registrations = [registry.find(1, 1).unwrap(), registry.find(1, 2).unwrap()];
+        let reports = run_parts(&registrations, &mut lines("1721\n979\n366\n299\n675\n1456"), Progress::new());
+        let answers: Vec<&str> = reports.iter().map(|r| r.result.as_deref().unwrap()).collect();
+        assert_eq!(answers, ["514579", "241861950"]);
+        assert_eq!(reports.iter().map(|r| r.timings.line_count).collect::<Vec<Option<usize>>>(), [Some(6), Some(6)]);
+    }
+
+    #[test]
+    fn parts_share_a_read_error() {
+        let registry = Registry::new();
+        let registrations = [registry.find(1, 1).unwrap(), registry.find(1, 2).unwrap()];
+        let mut lines = lines("1721\n979").chain(std::iter::once(Err(io::Error::other("disk on fire"))));
+        let reports = run_parts(&registrations, &mut lines, Progress::new());
+        assert_eq!(reports.len(), 2);
+        assert!(reports.iter().all(|r| matches!(&r.result, Err(RunError::Read(e)) if e.to_string() == "disk on fire")));
+    }
+
+    #[test]
+    fn a_solver_that_never_polls_is_given_up_on() {
+        let registration = stubs::registration(1, 1, stubs::never_finishing);
+        let start = Instant::now();
+        let report = run_with_time_limit(&registration, "1\n2".to_string(), Duration::from_millis(50), Progress::new());
+        assert!(start.elapsed() < Duration::from_secs(5));
+        let cancelled = matches!(&report.result, Err(RunError::Solve(SolveError::Cancelled(reason))) if reason.contains("50ms"));
+        assert!(cancelled);
+        assert_eq!((report.day_index, report.part_index), (1, 1));
+        let json = report.to_json().to_string();
+        assert!(json.starts_with(r#"{"day":1,"part":1,"#), "{}", json);
+        assert!(json.contains(r#""parse_time_ns":null,"solve_time_ns":"#), "{}", json);
+        assert!(json.ends_with(r#","input_lines":null}"#), "{}", json);
+    }
+
+    #[test]
+    fn a_solver_in_time_is_not_cancelled() {
+        let registration = stubs::registration(1, 1, stubs::sleeping::<10>);
+        let report = run_with_time_limit(&registration, "1\n2".to_string(), Duration::from_secs(10), Progress::new());
+        assert_eq!(report.result.as_deref().unwrap(), "10");
+        assert_eq!(report.timings.line_count, Some(2));
+    }
+
+    #[test]
+    fn a_panic_is_passed_on() {
+        let registration = stubs::registration(1, 1, stubs::panicking);
+        let run = || run_with_time_limit(&registration, String::new(), Duration::from_secs(10), Progress::new());
+        let payload =
+            match panic::catch_unwind(run) {
+                Err(payload) => payload,
+                Ok(_) => panic!("the panic wasn't passed on"),
+            };
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"the stub panicked"));
+    }
+
+    #[test]
+    fn strings_are_escaped() {
+        assert_eq!(quote("plain"), r#""plain""#);
+        assert_eq!(quote("say \"hi\"\\"), r#""say \"hi\"\\""#);
+        assert_eq!(quote("a\nb\r\tc"), r#""a\nb\r\tc""#);
+        assert_eq!(quote("\u{0}\u{1b}\u{1f} "), r#""\u0000\u001b\u001f ""#);
+        assert_eq!(quote("\u{7f}\u{e9}\u{1f600}"), "\"\u{7f}\u{e9}\u{1f600}\"");
+    }
+
+    #[test]
+    fn objects_keep_their_field_order() {
+        let object =
+            JsonObject::new()
+                .number("day", 1)
+                .null("answer")
+                .string("na\"me", "x")
+                .string("answer", "42")
+                .object("error", JsonObject::new());
+        assert_eq!(object.to_string(), r#"{"day":1,"answer":"42","na\"me":"x","error":{}}"#);
+    }
+
+    #[test]
+    fn parse_errors_say_where() {
+        let error = ParseError::new("1-3 a: \"abc\"", 7..12, "a password").on_line(4).in_group(1);
+        let json = error_to_json(&RunError::Parse(error)).to_string();
+        assert!(json.starts_with(r#"{"message":"#), "{}", json);
+        assert!(json.ends_with(concat!(r#""exit_code":3,"group":2,"line":5,"column":8,"end_column":13,"#,
+                                       r#""text":"1-3 a: \"abc\"","expected":"a password","found":"'\"abc\"'"}"#)),
+                "{}", json);
+    }
+
+    // Day 15 part 2 plays out 30 million turns, while part 1 only plays out 2020.
+    #[test]
+    fn parts_answered_in_time_keep_their_answers() {
+        let registry = Registry::new();
+        let day_registration = registry.find_day(15).unwrap();
+        let reports =
+            run_day_with_time_limit(day_registration, "0,3,6".to_string(), Duration::from_millis(50), Progress::new());
+        assert_eq!(reports[0].result.as_deref().unwrap(), "436");
+        assert!(matches!(reports[1].result, Err(RunError::Solve(SolveError::Cancelled(_)))));
+        assert_eq!(reports[0].timings.line_count, Some(1));
+        assert_eq!(reports[1].timings.line_count, None);
+    }
+
+}
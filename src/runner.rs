@@ -0,0 +1,152 @@
+use std::fmt;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use crate::days::{DaySolver, Solver, DAY_PART_COUNT};
+use crate::parse::ParseError;
+use crate::solve::{Progress, SolveError};
+
+#[derive(Debug)]
+pub enum RunError {
+    Read(io::Error),
+    Parse(ParseError),
+    Solve(SolveError),
+    Write(io::Error),
+}
+
+impl RunError {
+    // Each kind of failure gets its own exit code, so scripts can tell them apart.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            RunError::Read(_) => 2,
+            RunError::Parse(_) => 3,
+            RunError::Solve(SolveError::NoSolution(_)) => 4,
+            RunError::Solve(SolveError::PreconditionViolated(_)) => 5,
+            RunError::Solve(SolveError::LimitExceeded(_)) => 6,
+            RunError::Write(_) => 7,
+            RunError::Solve(SolveError::Cancelled(_)) => 8,
+        }
+    }
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunError::Read(e) => write!(f, "Read error: {}", e),
+            RunError::Parse(e) => write!(f, "{}", e),
+            RunError::Solve(e) => write!(f, "Failed to solve: {}", e),
+            RunError::Write(e) => write!(f, "Write error: {}", e),
+        }
+    }
+}
+
+// Reads the lines into the solver, and returns the serialized answer.
+pub fn solve(solver: &mut dyn Solver, lines: &mut dyn Iterator<Item = io::Result<String>>) -> Result<String, RunError> {
+    solver.read(lines)?;
+    solver.solve()
+}
+
+// Reads the lines into a solver for both parts, and returns the serialized answer to each part.
+pub fn solve_day(solver: &mut dyn DaySolver, lines: &mut dyn Iterator<Item = io::Result<String>>)
+                 -> Result<Vec<Result<String, RunError>>, RunError> {
+    solver.read(lines)?;
+    Ok((1..=DAY_PART_COUNT).map(|part_index| solver.solve(part_index)).collect())
+}
+
+// Like solve, but for input that's already in memory.
+pub fn solve_str(solver: &mut dyn Solver, input: &str) -> Result<String, RunError> {
+    solve(solver, &mut input.lines().map(|line| Ok(line.to_string())))
+}
+
+// How often the progress line is redrawn.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+// Does the work while drawing a line on standard error with how far along it is, and clears
+// the line after. Nothing is drawn until the work has taken a while and reported some progress.
+pub fn with_progress_line<T>(label: &str, progress: &Progress, work: impl FnOnce() -> T) -> T {
+    let finished = AtomicBool::new(false);
+    std::thread::scope(|scope| {
+        let drawer =
+            scope.spawn(|| {
+                let mut drawn = false;
+                loop {
+                    std::thread::park_timeout(PROGRESS_INTERVAL);
+                    if finished.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    if let Some(line) = progress_line(label, progress) {
+                        eprint!("\r{}\x1b[K", line);
+                        drawn = true;
+                    }
+                }
+                if drawn {
+                    eprint!("\r\x1b[K");
+                }
+            });
+        let _stop = StopDrawer { finished: &finished, drawer: drawer.thread() };
+        work()
+    })
+}
+
+// Stops the drawer once the work is over, even when it panics, since the scope waits for the drawer before passing
+// the panic on.
+struct StopDrawer<'a> {
+    finished: &'a AtomicBool,
+    drawer: &'a std::thread::Thread,
+}
+
+impl Drop for StopDrawer<'_> {
+    fn drop(&mut self) {
+        self.finished.store(true, Ordering::Relaxed);
+        self.drawer.unpark();
+    }
+}
+
+fn progress_line(label: &str, progress: &Progress) -> Option<String> {
+    let (done, total, unit) = progress.get()?;
+    Some(
+        match total {
+            None => format!("{}: {} {}", label, done, unit),
+            Some(total) => format!("{}: {}/{} {} ({}%)", label, done, total, unit, done * 100 / total),
+        }
+    )
+}
+
+// The conventional location of the input for a day, in the directory of its year.
+pub fn input_file_path(year: u32, day_index: u32) -> std::path::PathBuf {
+    std::path::PathBuf::from(format!("inputs/{}/day_{}.txt", year, day_index))
+}
+
+// The conventional location of the answer for a part of a day, in the directory of its year.
+pub fn output_file_path(year: u32, day_index: u32, part_index: u32) -> std::path::PathBuf {
+    std::path::PathBuf::from(format!("outputs/{}/day_{}-part_{}.txt", year, day_index, part_index))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::panic;
+    use std::sync::mpsc;
+
+    #[test]
+    fn a_panic_in_the_work_reaches_the_caller() {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let outcome =
+                panic::catch_unwind(|| {
+                    with_progress_line("Test", &Progress::new(), || -> () { panic!("the work panicked") })
+                });
+            let message = outcome.err().and_then(|payload| payload.downcast_ref::<&str>().map(|s| s.to_string()));
+            let _ = sender.send(message);
+        });
+        let message = receiver.recv_timeout(Duration::from_secs(10)).expect("the progress line kept waiting");
+        assert_eq!(message.as_deref(), Some("the work panicked"));
+    }
+
+    #[test]
+    fn the_result_is_passed_on() {
+        assert_eq!(with_progress_line("Test", &Progress::new(), || 42), 42);
+    }
+
+}
@@ -0,0 +1,501 @@
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::events::Event;
+
+// Which adapter the new day's solvers are written for.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Kind {
+    // One line at a time, with CumulativeSolver.
+    Cumulative,
+    // All the lines at once, with FixedLineCountSolver.
+    WholeInput,
+}
+
+impl Kind {
+    pub fn from_name(name: &str) -> Option<Kind> {
+        match name {
+            "cumulative" => Some(Kind::Cumulative),
+            "whole-input" => Some(Kind::WholeInput),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ScaffoldError {
+    DayExists(PathBuf),
+    Read(PathBuf, io::Error),
+    Write(PathBuf, io::Error),
+    // The days module doesn't look the way it's expected to, so the new day can't be slotted in.
+    RegistryUnrecognized(PathBuf),
+}
+
+impl fmt::Display for ScaffoldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScaffoldError::DayExists(path) => write!(f, "The day already exists: '{}'.", path.display()),
+            ScaffoldError::Read(path, e) => write!(f, "Failed to read '{}': {}.", path.display(), e),
+            ScaffoldError::Write(path, e) => write!(f, "Failed to write '{}': {}.", path.display(), e),
+            ScaffoldError::RegistryUnrecognized(path) =>
+                write!(f, "No day modules and registrations found in '{}'.", path.display()),
+        }
+    }
+}
+
+const CUMULATIVE_TEMPLATE: &str = "\
+use super::CumulativeSolver;
+use super::{Cumulative, CumulativeDay, DayRegistration, Registration, Registry, SolverKind};
+use crate::parse::ParseError;
+use crate::solve::SolveError;
+
+const TITLE: &str = \"Day {day}\";
+
+fn deserialize_line(line: &str) -> Result<String, ParseError> {
+    Ok(line.to_string())
+}
+
+pub mod part_1 {
+
+    use super::*;
+
+    pub struct State {
+        lines: std::vec::Vec<String>,
+    }
+
+    impl super::CumulativeSolver for State {
+
+        type Input = String;
+        type Output = u64;
+
+        fn deserialize(&self, line: &str) -> Result<String, ParseError> {
+            deserialize_line(line)
+        }
+
+        fn serialize(&self, output: &u64) -> std::string::String {
+            output.to_string()
+        }
+
+        fn new() -> Self {
+            State {
+                lines: std::vec::Vec::new(),
+            }
+        }
+
+        fn accumulate(&mut self, line: String) -> Option<u64> {
+            self.lines.push(line);
+            None
+        }
+
+        fn solve(&mut self) -> Result<u64, SolveError> {
+            Err(SolveError::PreconditionViolated(format!(\"part 1 is not solved yet, after {} lines\", self.lines.len())))
+        }
+
+    }
+
+}
+
+pub mod part_2 {
+
+    use super::*;
+
+    pub struct State {
+        lines: std::vec::Vec<String>,
+    }
+
+    impl super::CumulativeSolver for State {
+
+        type Input = String;
+        type Output = u64;
+
+        fn deserialize(&self, line: &str) -> Result<String, ParseError> {
+            deserialize_line(line)
+        }
+
+        fn serialize(&self, output: &u64) -> std::string::String {
+            output.to_string()
+        }
+
+        fn new() -> Self {
+            State {
+                lines: std::vec::Vec::new(),
+            }
+        }
+
+        fn accumulate(&mut self, line: String) -> Option<u64> {
+            self.lines.push(line);
+            None
+        }
+
+        fn solve(&mut self) -> Result<u64, SolveError> {
+            Err(SolveError::PreconditionViolated(format!(\"part 2 is not solved yet, after {} lines\", self.lines.len())))
+        }
+
+    }
+
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.add(
+        Registration {
+            day_index: {day},
+            part_index: 1,
+            title: TITLE,
+            kind: SolverKind::Cumulative,
+            solver: || Box::new(Cumulative::<part_1::State>::new()),
+        }
+    );
+    registry.add(
+        Registration {
+            day_index: {day},
+            part_index: 2,
+            title: TITLE,
+            kind: SolverKind::Cumulative,
+            solver: || Box::new(Cumulative::<part_2::State>::new()),
+        }
+    );
+    registry.add_day(
+        DayRegistration {
+            day_index: {day},
+            title: TITLE,
+            solver: || Box::new(CumulativeDay::<part_1::State, part_2::State>::new()),
+        }
+    );
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::days::{solve_day_example, solve_example};
+
+    // The example from the puzzle.
+    const EXAMPLE: &str = \"\";
+
+    #[test]
+    #[ignore]
+    fn part_1_example() {
+        assert_eq!(solve_example(Cumulative::<part_1::State>::new(), EXAMPLE), \"\");
+    }
+
+    #[test]
+    #[ignore]
+    fn part_2_example() {
+        assert_eq!(solve_example(Cumulative::<part_2::State>::new(), EXAMPLE), \"\");
+    }
+
+    #[test]
+    #[ignore]
+    fn both_parts_example() {
+        assert_eq!(solve_day_example(CumulativeDay::<part_1::State, part_2::State>::new(), EXAMPLE), [\"\", \"\"]);
+    }
+
+}
+";
+
+const WHOLE_INPUT_TEMPLATE: &str = "\
+use super::FixedLineCountSolver;
+use super::{DayRegistration, FixedLineCount, FixedLineCountDay, Registration, Registry, SolverKind};
+use crate::parse::ParseError;
+use crate::solve::SolveError;
+
+const TITLE: &str = \"Day {day}\";
+
+fn from_input(lines: &[String]) -> Result<Vec<String>, ParseError> {
+    Ok(lines.to_vec())
+}
+
+pub mod part_1 {
+
+    use super::*;
+
+    pub struct Instance {
+        pub(super) lines: Vec<String>,
+    }
+
+    impl super::FixedLineCountSolver for Instance {
+
+        type Output = u64;
+
+        fn from_input(lines: &[String]) -> Result<Instance, ParseError> {
+            Ok(
+                Instance {
+                    lines: super::from_input(lines)?,
+                }
+            )
+        }
+
+        fn solve(&mut self) -> Result<u64, SolveError> {
+            Err(SolveError::PreconditionViolated(format!(\"part 1 is not solved yet, after {} lines\", self.lines.len())))
+        }
+
+    }
+
+}
+
+pub mod part_2 {
+
+    use super::*;
+
+    pub struct Instance {
+        lines: Vec<String>,
+    }
+
+    impl super::FixedLineCountSolver for Instance {
+
+        type Output = u64;
+
+        fn from_input(lines: &[String]) -> Result<Instance, ParseError> {
+            Ok(
+                Instance {
+                    lines: super::from_input(lines)?,
+                }
+            )
+        }
+
+        fn solve(&mut self) -> Result<u64, SolveError> {
+            Err(SolveError::PreconditionViolated(format!(\"part 2 is not solved yet, after {} lines\", self.lines.len())))
+        }
+
+    }
+
+    // Solving both parts at once parses the input only for part 1.
+    impl From<&super::part_1::Instance> for Instance {
+        fn from(part_1: &super::part_1::Instance) -> Instance {
+            Instance {
+                lines: part_1.lines.clone(),
+            }
+        }
+    }
+
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.add(
+        Registration {
+            day_index: {day},
+            part_index: 1,
+            title: TITLE,
+            kind: SolverKind::FixedLineCount,
+            solver: || Box::new(FixedLineCount::<part_1::Instance>::new()),
+        }
+    );
+    registry.add(
+        Registration {
+            day_index: {day},
+            part_index: 2,
+            title: TITLE,
+            kind: SolverKind::FixedLineCount,
+            solver: || Box::new(FixedLineCount::<part_2::Instance>::new()),
+        }
+    );
+    registry.add_day(
+        DayRegistration {
+            day_index: {day},
+            title: TITLE,
+            solver: || Box::new(FixedLineCountDay::<part_1::Instance, part_2::Instance>::new()),
+        }
+    );
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::days::{solve_day_example, solve_example};
+
+    // The example from the puzzle.
+    const EXAMPLE: &str = \"\";
+
+    #[test]
+    #[ignore]
+    fn part_1_example() {
+        assert_eq!(solve_example(FixedLineCount::<part_1::Instance>::new(), EXAMPLE), \"\");
+    }
+
+    #[test]
+    #[ignore]
+    fn part_2_example() {
+        assert_eq!(solve_example(FixedLineCount::<part_2::Instance>::new(), EXAMPLE), \"\");
+    }
+
+    #[test]
+    #[ignore]
+    fn both_parts_example() {
+        assert_eq!(solve_day_example(FixedLineCountDay::<part_1::Instance, part_2::Instance>::new(), EXAMPLE), [\"\", \"\"]);
+    }
+
+}
+";
+
+// The source of a new day's module, with solvers that don't solve anything yet and ignored example tests.
+pub fn day_module(day_index: u32, kind: Kind) -> String {
+    let template =
+        match kind {
+            Kind::Cumulative => CUMULATIVE_TEMPLATE,
+            Kind::WholeInput => WHOLE_INPUT_TEMPLATE,
+        };
+    template.replace("{day}", &day_index.to_string())
+}
+
+// Finds the day in a line like "<prefix><day><suffix>", after trimming the indentation.
+fn line_day_index(line: &str, prefix: &str, suffix: &str) -> Option<u32> {
+    line.trim().strip_prefix(prefix)?.strip_suffix(suffix)?.parse().ok()
+}
+
+// Inserts a line for the day among the lines for other days, keeping them in order of day.
+// Returns nothing if there are no lines for other days to go by.
+fn insert_day_line(lines: &mut Vec<String>, day_index: u32, prefix: &str, suffix: &str) -> Option<()> {
+    let day_lines: Vec<(usize, u32)> =
+        lines.iter().enumerate()
+            .filter_map(|(line_index, line)| Some((line_index, line_day_index(line, prefix, suffix)?)))
+            .collect();
+    let (last_line_index, _) = *day_lines.last()?;
+    let (line_index, indentation) =
+        match day_lines.iter().find(|(_, other_day_index)| *other_day_index > day_index) {
+            Some((line_index, _)) => (*line_index, &lines[*line_index]),
+            None => (last_line_index + 1, &lines[last_line_index]),
+        };
+    let indentation = &indentation[..(indentation.len() - indentation.trim_start().len())];
+    let line = format!("{}{}{}{}", indentation, prefix, day_index, suffix);
+    lines.insert(line_index, line);
+    Some(())
+}
+
+// Whether the source ends its lines with "\r\n" or "\n", going by its first line.
+fn line_ending(source: &str) -> &'static str {
+    match source.find('\n') {
+        Some(index) if source[..index].ends_with('\r') => "\r\n",
+        _ => "\n",
+    }
+}
+
+// Adds the module declaration and the registration of the day to the source of the days module.
+// The lines keep the line ending of the source.
+pub fn register_day(source: &str, day_index: u32) -> Option<String> {
+    let mut lines: Vec<String> = source.lines().map(|line| line.to_string()).collect();
+    insert_day_line(&mut lines, day_index, "pub mod day_", ";")?;
+    insert_day_line(&mut lines, day_index, "day_", "::register(&mut registry);")?;
+    let ending = line_ending(source);
+    let mut registered = lines.join(ending);
+    if source.ends_with('\n') {
+        registered.push_str(ending);
+    }
+    Some(registered)
+}
+
+// What was made for a new day.
+pub struct Scaffold {
+    pub module_file_path: PathBuf,
+    pub input_file_path: PathBuf,
+    pub registry_file_path: PathBuf,
+}
+
+// Writes the new day's module and an empty input under the repository root, and registers the day in its event.
+// Nothing is written if the day's module exists already, or if the days module can't be updated.
+pub fn new_day(root: &Path, event: &Event, day_index: u32, kind: Kind) -> Result<Scaffold, ScaffoldError> {
+    let days_directory_path = root.join(event.days_directory);
+    let module_file_path = days_directory_path.join(format!("day_{}.rs", day_index));
+    let registry_file_path = days_directory_path.join("mod.rs");
+    let input_file_path = root.join(crate::runner::input_file_path(event.year, day_index));
+    if module_file_path.exists() {
+        return Err(ScaffoldError::DayExists(module_file_path));
+    }
+    let registry_source =
+        std::fs::read_to_string(&registry_file_path).map_err(|e| ScaffoldError::Read(registry_file_path.clone(), e))?;
+    let registered_source =
+        register_day(&registry_source, day_index).ok_or_else(|| {
+            ScaffoldError::RegistryUnrecognized(registry_file_path.clone())
+        })?;
+    // The new module ends its lines like the days module does.
+    let module_source = day_module(day_index, kind).replace('\n', line_ending(&registry_source));
+    std::fs::write(&module_file_path, module_source)
+        .map_err(|e| ScaffoldError::Write(module_file_path.clone(), e))?;
+    // An input that's already there was likely downloaded, so it's kept.
+    if !input_file_path.exists() {
+        input_file_path.parent().map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&input_file_path, ""))
+            .map_err(|e| ScaffoldError::Write(input_file_path.clone(), e))?;
+    }
+    std::fs::write(&registry_file_path, registered_source)
+        .map_err(|e| ScaffoldError::Write(registry_file_path.clone(), e))?;
+    Ok(
+        Scaffold {
+            module_file_path,
+            input_file_path,
+            registry_file_path,
+        }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const REGISTRY: &str = "\
+pub mod day_1;
+pub mod day_12;
+
+impl Registry {
+    pub fn new() -> Self {
+        let mut registry = Registry::new();
+        day_1::register(&mut registry);
+        day_12::register(&mut registry);
+        registry
+    }
+}
+";
+
+    #[test]
+    fn registers_between_days() {
+        let registered = register_day(REGISTRY, 5).unwrap();
+        assert!(registered.contains("pub mod day_1;\npub mod day_5;\npub mod day_12;\n"));
+        assert!(registered.contains("        day_1::register(&mut registry);\n        day_5::register(&mut registry);\n"));
+    }
+
+    #[test]
+    fn registers_before_and_after_every_day() {
+        let registered = register_day(&register_day(REGISTRY, 25).unwrap(), 0).unwrap();
+        assert!(registered.starts_with("pub mod day_0;\npub mod day_1;\npub mod day_12;\npub mod day_25;\n"));
+        assert!(registered.contains("        day_12::register(&mut registry);\n        day_25::register(&mut registry);\n"));
+        assert!(registered.contains("Self {\n        let mut registry = Registry::new();\n        day_0::register"));
+    }
+
+    #[test]
+    fn line_endings_are_kept() {
+        let registered = register_day(&REGISTRY.replace('\n', "\r\n"), 5).unwrap();
+        assert_eq!(registered, register_day(REGISTRY, 5).unwrap().replace('\n', "\r\n"));
+        assert!(!registered.replace("\r\n", "").contains('\n'));
+    }
+
+    #[test]
+    fn days_module_without_days_is_unrecognized() {
+        assert!(register_day("pub mod other;\n", 5).is_none());
+    }
+
+    #[test]
+    fn module_is_for_the_day() {
+        for kind in [Kind::Cumulative, Kind::WholeInput] {
+            let module = day_module(7, kind);
+            assert!(!module.contains("{day}"));
+            assert_eq!(module.matches("day_index: 7,").count(), 3);
+            // Not being solved yet isn't an answer to the puzzle, so it mustn't look like there's no solution.
+            assert!(!module.contains("NoSolution"));
+        }
+    }
+
+    #[test]
+    fn existing_day_is_kept() {
+        let root = std::env::temp_dir().join(format!("aoc2020-scaffold-{}", std::process::id()));
+        let event = crate::events::find(crate::events::DEFAULT_YEAR).unwrap();
+        std::fs::create_dir_all(root.join("src").join("days")).unwrap();
+        std::fs::write(root.join("src").join("days").join("day_1.rs"), "// Solved.\n").unwrap();
+        let result = new_day(&root, event, 1, Kind::Cumulative);
+        let module = std::fs::read_to_string(root.join("src").join("days").join("day_1.rs")).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+        assert!(matches!(result, Err(ScaffoldError::DayExists(_))));
+        assert_eq!(module, "// Solved.\n");
+    }
+
+}
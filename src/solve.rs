@@ -0,0 +1,108 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+// Why a solver that got valid input still couldn't produce an answer.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SolveError {
+    // The input is fine, but the puzzle has no answer for it.
+    NoSolution(String),
+    // The input parsed, but breaks an assumption the solver relies on.
+    PreconditionViolated(String),
+    // An answer would take more time, space or bits than allowed.
+    LimitExceeded(String),
+    // The solver was asked to stop before it found an answer, like when it ran out of time.
+    Cancelled(String),
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::NoSolution(reason) => write!(f, "No solution: {}.", reason),
+            SolveError::PreconditionViolated(reason) => write!(f, "Precondition violated: {}.", reason),
+            SolveError::LimitExceeded(reason) => write!(f, "Limit exceeded: {}.", reason),
+            SolveError::Cancelled(reason) => write!(f, "Cancelled: {}.", reason),
+        }
+    }
+}
+
+// Lets a solver be asked to stop from another thread. Clones share the same flag.
+// Solvers with long loops poll it, so nothing here can stop a solver that doesn't.
+#[derive(Clone, Debug, Default)]
+pub struct Cancellation {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Cancellation {
+
+    pub fn new() -> Self {
+        Cancellation {
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    // Fails once cancelled, so a loop can poll it with ?.
+    pub fn check(&self) -> Result<(), SolveError> {
+        if self.is_cancelled() {
+            return Err(SolveError::Cancelled("the solver was asked to stop".to_string()));
+        }
+        Ok(())
+    }
+
+}
+
+// Lets a solver say how far along it is, for another thread to show. Clones share the same counts.
+#[derive(Clone, Debug, Default)]
+pub struct Progress {
+    shared: Arc<SharedProgress>,
+}
+
+#[derive(Debug, Default)]
+struct SharedProgress {
+    done: AtomicU64,
+    // Zero when the total isn't known.
+    total: AtomicU64,
+    // Empty until the solver starts reporting.
+    unit: Mutex<&'static str>,
+}
+
+impl Progress {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Starts counting from zero, like for the turns of a game or the instructions executed.
+    pub fn start(&self, total: Option<u64>, unit: &'static str) {
+        self.shared.done.store(0, Ordering::Relaxed);
+        self.shared.total.store(total.unwrap_or(0), Ordering::Relaxed);
+        *self.shared.unit.lock().unwrap() = unit;
+    }
+
+    pub fn set(&self, done: u64) {
+        self.shared.done.store(done, Ordering::Relaxed);
+    }
+
+    pub fn advance(&self, by: u64) {
+        self.shared.done.fetch_add(by, Ordering::Relaxed);
+    }
+
+    // How much is done, out of how much, and of what. Nothing if the solver hasn't started reporting.
+    pub fn get(&self) -> Option<(u64, Option<u64>, &'static str)> {
+        let unit = *self.shared.unit.lock().unwrap();
+        if unit.is_empty() {
+            return None;
+        }
+        let total = self.shared.total.load(Ordering::Relaxed);
+        Some((self.shared.done.load(Ordering::Relaxed), if total == 0 { None } else { Some(total) }, unit))
+    }
+
+}
@@ -0,0 +1,89 @@
+use aoc2020::days::Registry;
+use aoc2020::generate::{self, Random};
+use aoc2020::runner::{self, RunError};
+use aoc2020::solve::SolveError;
+
+// How many seeds every generator gets.
+const SEED_COUNT: u64 = 5;
+
+fn lines(input: &str) -> impl Iterator<Item = std::io::Result<String>> + '_ {
+    input.lines().map(|line| Ok(line.to_string()))
+}
+
+// Day 15 part 2 plays out 30 million turns whatever the input, which is too slow to do for every seed.
+fn is_slow(day_index: u32, part_index: u32) -> bool {
+    day_index == 15 && part_index == 2
+}
+
+#[test]
+fn generated_inputs_are_solved() {
+    let registry = Registry::new();
+    for generator in &generate::GENERATORS {
+        for seed in 1..=SEED_COUNT {
+            let input = generate::generate(generator.day_index, generator.default_size, seed).unwrap();
+            for part_index in 1..=2 {
+                if is_slow(generator.day_index, part_index) {
+                    continue;
+                }
+                if let Some(registration) = registry.find(generator.day_index, part_index) {
+                    let result = runner::solve_str((registration.solver)().as_mut(), &input);
+                    assert!(result.is_ok(), "day {} part {} failed on seed {}: {}",
+                            generator.day_index, part_index, seed, result.unwrap_err());
+                }
+            }
+        }
+    }
+}
+
+// Solving both parts at once reads the input only once, which mustn't change the answers.
+#[test]
+fn day_solvers_agree_with_part_solvers() {
+    let registry = Registry::new();
+    for generator in &generate::GENERATORS {
+        let day_registration =
+            match registry.find_day(generator.day_index) {
+                None => continue,
+                Some(r) => r,
+            };
+        if is_slow(generator.day_index, 2) {
+            continue;
+        }
+        for seed in 1..=SEED_COUNT {
+            let input = generate::generate(generator.day_index, generator.default_size, seed).unwrap();
+            let day_answers: Vec<String> =
+                runner::solve_day((day_registration.solver)().as_mut(), &mut lines(&input)).unwrap()
+                    .into_iter().map(|answer| answer.unwrap()).collect();
+            let part_answers: Vec<String> =
+                (1..=2).map(|part_index| {
+                    let registration = registry.find(generator.day_index, part_index).unwrap();
+                    runner::solve_str((registration.solver)().as_mut(), &input).unwrap()
+                }).collect();
+            assert_eq!(day_answers, part_answers, "day {} on seed {}", generator.day_index, seed);
+        }
+    }
+}
+
+#[test]
+fn generated_inputs_depend_only_on_the_seed() {
+    for generator in &generate::GENERATORS {
+        let first = generate::generate(generator.day_index, generator.default_size, 1).unwrap();
+        assert_eq!(generate::generate(generator.day_index, generator.default_size, 1).unwrap(), first);
+        assert_ne!(generate::generate(generator.day_index, generator.default_size, 2).unwrap(), first);
+    }
+}
+
+#[test]
+fn terminating_day_8_programs_have_no_loop() {
+    let registry = Registry::new();
+    let registration = registry.find(8, 1).unwrap();
+    for seed in 1..=SEED_COUNT {
+        let input = generate::day_8_terminating(&mut Random::new(seed), 650);
+        let result = runner::solve_str((registration.solver)().as_mut(), &input);
+        assert!(matches!(result, Err(RunError::Solve(SolveError::NoSolution(_)))), "seed {}: {:?}", seed, result);
+    }
+}
+
+#[test]
+fn no_generator_for_a_day_without_one() {
+    assert!(generate::generate(4, 10, 1).is_none());
+}
@@ -0,0 +1,87 @@
+use std::path::{Path, PathBuf};
+use aoc2020::days::Registry;
+use aoc2020::events;
+use aoc2020::runner;
+
+// A stored answer, named like outputs/<year>/day_<day>-part_<part>.txt.
+struct Case {
+    year: u32,
+    day_index: u32,
+    part_index: u32,
+    output_file_path: PathBuf,
+}
+
+fn package_path(relative_path: &Path) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(relative_path)
+}
+
+fn read_case(year: u32, output_file_path: PathBuf) -> Option<Case> {
+    let name = output_file_path.file_stem()?.to_str()?;
+    let (day, part) = name.strip_prefix("day_")?.split_once("-part_")?;
+    Some(
+        Case {
+            year,
+            day_index: day.parse().ok()?,
+            part_index: part.parse().ok()?,
+            output_file_path,
+        }
+    )
+}
+
+// A year without any stored answers yet has no directory, and no cases.
+fn read_cases(year: u32) -> Vec<Case> {
+    let output_directory_path = package_path(Path::new("outputs")).join(year.to_string());
+    let entries =
+        match std::fs::read_dir(&output_directory_path) {
+            Err(_) => return Vec::new(),
+            Ok(entries) => entries,
+        };
+    let mut cases: Vec<Case> =
+        entries
+            .map(|entry| entry.unwrap().path())
+            .filter_map(|output_file_path| read_case(year, output_file_path))
+            .collect();
+    cases.sort_by_key(|case| (case.day_index, case.part_index));
+    cases
+}
+
+// Returns what's wrong, if anything.
+fn run_case(registry: &Registry, case: &Case) -> Option<String> {
+    let registration =
+        match registry.find(case.day_index, case.part_index) {
+            None => return Some("no solver is registered".to_string()),
+            Some(r) => r,
+        };
+    let input_file_path = package_path(&runner::input_file_path(case.year, case.day_index));
+    let input =
+        match std::fs::read_to_string(&input_file_path) {
+            Err(e) => return Some(format!("{}: {}", input_file_path.display(), e)),
+            Ok(s) => s,
+        };
+    let expected = std::fs::read_to_string(&case.output_file_path).unwrap().trim().to_string();
+    match runner::solve_str((registration.solver)().as_mut(), &input) {
+        Err(e) => Some(e.to_string()),
+        Ok(actual) if actual != expected => Some(format!("expected {}, got {}", expected, actual)),
+        Ok(_) => None,
+    }
+}
+
+// Every stored answer is a case, so a new day is covered as soon as its input and output are added.
+#[test]
+fn stored_answers() {
+    let mut cases = Vec::new();
+    let mut failures = Vec::new();
+    for event in &events::EVENTS {
+        let registry = (event.registry)();
+        let year_cases = read_cases(event.year);
+        failures.extend(year_cases.iter().filter_map(|case| {
+            run_case(&registry, case).map(|failure| {
+                format!("{} day {} part {}: {}", case.year, case.day_index, case.part_index, failure)
+            })
+        }));
+        cases.extend(year_cases);
+    }
+    assert!(!cases.is_empty(), "there are no stored answers in outputs/");
+    assert!(failures.is_empty(), "{} of {} stored answers failed:\n{}",
+            failures.len(), cases.len(), failures.join("\n"));
+}
@@ -0,0 +1,168 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+use aoc2020::days::{self, Registration, Registry};
+use aoc2020::generate::Random;
+use aoc2020::report::{self, Report};
+use aoc2020::runner::{self, RunError};
+use aoc2020::solve::{Cancellation, Progress};
+
+// How many inputs of each kind every solver gets.
+const CASE_COUNT: usize = 500;
+
+// Day 15 part 2 always plays out 30 million turns, whatever the input, so it's cancelled after this long.
+// Every other solver runs until it's done.
+const SLOW_SOLVE_TIME_LIMIT: Duration = Duration::from_millis(20);
+
+fn is_slow(registration: &Registration) -> bool {
+    registration.day_index == 15 && registration.part_index == 2
+}
+
+// Pieces of every puzzle's input, so random inputs get close enough to valid to reach the solvers.
+const TOKENS: [&str; 34] = ["0", "1", "2", "7", "9", "10", "42", "2147483647", "-2147483648", "4294967295", "4294967296",
+                            "-", "+", " ", ": ", ",", "x", "\n", "\n\n", "",
+                            "a", "b", "z", "#", ".", "acc", "jmp", "nop", "F", "N", "E", "L", "R", "\u{e9}"];
+
+fn random_bytes(random: &mut Random) -> String {
+    let length = random.index(64);
+    let bytes: Vec<u8> = (0..length).map(|_| random.number() as u8).collect();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn random_tokens(random: &mut Random) -> String {
+    let length = random.index(32);
+    (0..length).map(|_| TOKENS[random.index(TOKENS.len())]).collect()
+}
+
+// Takes a few lines of a real input, and replaces, removes or inserts a token somewhere in them.
+fn mutated_input(random: &mut Random, input: &str) -> String {
+    let lines: Vec<&str> = input.lines().collect();
+    let start = random.index(lines.len().max(1));
+    let mut text = lines.iter().skip(start).take(1 + random.index(4)).cloned().collect::<Vec<&str>>().join("\n");
+    let mut index = random.index(text.len() + 1);
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    let end = text[index..].chars().next().map_or(index, |c| index + c.len_utf8());
+    match random.index(3) {
+        0 => text.replace_range(index..end, TOKENS[random.index(TOKENS.len())]),
+        1 => text.replace_range(index..end, ""),
+        _ => text.insert_str(index, TOKENS[random.index(TOKENS.len())]),
+    }
+    text
+}
+
+// The solver runs on the test thread, so a panic is caught wherever it happens. Only a slow solver is cancelled, from
+// another thread, and then it's still waited for until it stops.
+fn run_without_panic(registration: &Registration, input: &str) -> Report {
+    let cancellation = Cancellation::new();
+    let run =
+        AssertUnwindSafe(|| {
+            report::run_with(registration, &mut input.lines().map(|line| Ok(line.to_string())),
+                             cancellation.clone(), Progress::new())
+        });
+    let outcome =
+        if is_slow(registration) {
+            std::thread::scope(|scope| {
+                let (sender, receiver) = mpsc::channel::<()>();
+                let timer_cancellation = cancellation.clone();
+                scope.spawn(move || {
+                    if let Err(RecvTimeoutError::Timeout) = receiver.recv_timeout(SLOW_SOLVE_TIME_LIMIT) {
+                        timer_cancellation.cancel();
+                    }
+                });
+                let outcome = panic::catch_unwind(run);
+                drop(sender);
+                outcome
+            })
+        } else {
+            panic::catch_unwind(run)
+        };
+    match outcome {
+        Err(_) => panic!("day {} part {} panicked on input {:?}", registration.day_index, registration.part_index, input),
+        Ok(report) => report,
+    }
+}
+
+fn for_every_solver(seed: u64, generate: impl Fn(&mut Random, &Registration) -> String) {
+    let registry = Registry::new();
+    for registration in registry.registrations() {
+        let mut random = Random::new(seed + registration.day_index as u64 * 100 + registration.part_index as u64);
+        for _ in 0..CASE_COUNT {
+            let input = generate(&mut random, registration);
+            run_without_panic(registration, &input);
+        }
+    }
+}
+
+#[test]
+fn random_bytes_never_panic() {
+    for_every_solver(1, |random, _| random_bytes(random));
+}
+
+#[test]
+fn random_tokens_never_panic() {
+    for_every_solver(2, |random, _| random_tokens(random));
+}
+
+#[test]
+fn mutated_inputs_never_panic() {
+    for_every_solver(3, |random, registration| {
+        let input = std::fs::read_to_string(runner::input_file_path(days::YEAR, registration.day_index)).unwrap_or_default();
+        mutated_input(random, &input)
+    });
+}
+
+// No puzzle uses the replacement character, so a first line with one is a parse error for every day.
+#[test]
+fn invalid_bytes_are_parse_errors() {
+    for_every_solver(4, |random, _| {
+        let mut input = random_tokens(random).replace('\n', "");
+        let char_index = random.index(input.chars().count() + 1);
+        let index = input.char_indices().nth(char_index).map_or(input.len(), |(index, _)| index);
+        input.insert(index, char::REPLACEMENT_CHARACTER);
+        input
+    });
+    let registry = Registry::new();
+    let mut random = Random::new(5);
+    for registration in registry.registrations() {
+        for _ in 0..CASE_COUNT {
+            let mut input = random_bytes(&mut random).replace(['\n', '\r'], "");
+            input.push(char::REPLACEMENT_CHARACTER);
+            let report = run_without_panic(registration, &input);
+            assert!(matches!(report.result, Err(RunError::Parse(_))),
+                    "day {} part {} didn't give a parse error on {:?}",
+                    registration.day_index, registration.part_index, input);
+        }
+    }
+}
+
+// Programs with arguments near the limits of 32 bits, which day 8 part 2 runs once for every jmp or nop it changes.
+fn random_program(random: &mut Random) -> String {
+    const ARGUMENTS: [i32; 8] = [0, 1, -1, 2, -2, i32::MAX, i32::MIN, i32::MIN + 1];
+    let length = 1 + random.index(16);
+    (0..length).map(|_| {
+        let operation = ["acc", "jmp", "nop"][random.index(3)];
+        let argument =
+            match random.index(3) {
+                0 => ARGUMENTS[random.index(ARGUMENTS.len())],
+                1 => random.index(2 * length + 1) as i32 - length as i32,
+                _ => random.number() as i32,
+            };
+        format!("{} {:+}\n", operation, argument)
+    }).collect()
+}
+
+#[test]
+fn random_programs_never_panic() {
+    let registry = Registry::new();
+    let mut random = Random::new(6);
+    for part_index in 1..=2 {
+        let registration = registry.find(8, part_index).unwrap();
+        for _ in 0..CASE_COUNT {
+            let input = random_program(&mut random);
+            let report = run_without_panic(registration, &input);
+            assert!(!matches!(report.result, Err(RunError::Parse(_))), "part {} didn't parse {:?}", part_index, input);
+        }
+    }
+}